@@ -26,6 +26,9 @@ fn main() -> eframe::Result {
 struct MyApp {
     /// Behind an `Arc<Mutex<…>>` so we can pass it to [`egui::PaintCallback`] and paint later.
     drawer: Arc<Mutex<del_glow::drawer_vtx2xyrgb::Drawer>>,
+    /// camera of the drawers. The triangle is given in the normalized device coordinate,
+    /// so the default (identity) camera is uploaded
+    frame_uniforms: Arc<Mutex<del_glow::frame_uniforms::FrameUniformBuffer>>,
 }
 
 impl MyApp {
@@ -34,18 +37,20 @@ impl MyApp {
             .gl
             .as_ref()
             .expect("You need to run eframe with the glow backend");
-        let mut drawer = del_glow::drawer_vtx2xyrgb::Drawer {
-            program: None,
-            mode: glow::TRIANGLES,
-            vertex_array: None,
-        };
-        drawer.compile_shader(&gl);
+        // shared by all the drawers so that the ones with the same sources share the program
+        let mut shader_builder = del_glow::shader_builder::ShaderBuilder::new();
+        let mut drawer = del_glow::drawer_vtx2xyrgb::Drawer::new();
+        drawer.mode = glow::TRIANGLES;
+        drawer.compile_shader(gl, &mut shader_builder);
         let vtx2xyrgb: [f32; 15] = [
             -0.5, -0.5, 1.0, 0.0, 0.0, 0.0, 0.5, 0.0, 1.0, 0.0, 0.5, -0.5, 0.0, 0.0, 1.0,
         ];
-        drawer.set_vtx2xyrgb(&gl, &vtx2xyrgb);
+        drawer.set_vtx2xyrgb(gl, &vtx2xyrgb).unwrap();
+        let mut frame_uniforms = del_glow::frame_uniforms::FrameUniformBuffer::new();
+        frame_uniforms.init(gl);
         Self {
             drawer: Arc::new(Mutex::new(drawer)),
+            frame_uniforms: Arc::new(Mutex::new(frame_uniforms)),
         }
     }
 }
@@ -70,6 +75,7 @@ impl eframe::App for MyApp {
     fn on_exit(&mut self, gl: Option<&glow::Context>) {
         if let Some(gl) = gl {
             self.drawer.lock().destroy(gl);
+            self.frame_uniforms.lock().destroy(gl);
         }
     }
 }
@@ -78,9 +84,12 @@ impl MyApp {
     fn custom_painting(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
         // Clone locals so we can move them into the paint callback:
         let drawer = self.drawer.clone();
+        let frame_uniforms = self.frame_uniforms.clone();
         let callback = egui::PaintCallback {
             rect,
             callback: std::sync::Arc::new(egui_glow::CallbackFn::new(move |_info, painter| {
+                let uniforms = del_glow::frame_uniforms::FrameUniforms::default();
+                frame_uniforms.lock().update(painter.gl(), &uniforms);
                 drawer.lock().paint(painter.gl());
             })),
         };
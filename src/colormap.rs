@@ -0,0 +1,72 @@
+//! colormaps evaluated on the CPU. They match the ones of `#include "colormap"`
+//! in [`crate::shader_builder::SNIPPETS`]
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    Grayscale,
+    Jet,
+    Viridis,
+    Coolwarm,
+}
+
+impl Colormap {
+    /// `t` in [0,1] (clamped)
+    pub fn eval(&self, t: f32) -> [f32; 3] {
+        match self {
+            Colormap::Grayscale => grayscale(t),
+            Colormap::Jet => jet(t),
+            Colormap::Viridis => viridis(t),
+            Colormap::Coolwarm => coolwarm(t),
+        }
+    }
+
+    /// index passed to `colormap(int, float)` of the GLSL snippet
+    pub fn glsl_index(&self) -> i32 {
+        match self {
+            Colormap::Grayscale => 0,
+            Colormap::Jet => 1,
+            Colormap::Viridis => 2,
+            Colormap::Coolwarm => 3,
+        }
+    }
+}
+
+/// `t` in [0,1] (clamped)
+pub fn grayscale(t: f32) -> [f32; 3] {
+    [t.clamp(0.0, 1.0); 3]
+}
+
+/// `t` in [0,1] (clamped)
+pub fn jet(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0);
+    [3.0, 2.0, 1.0].map(|a: f32| (1.5 - (4.0 * t - a).abs()).clamp(0.0, 1.0))
+}
+
+/// polynomial fit of matplotlib's viridis. `t` in [0,1] (clamped)
+pub fn viridis(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0);
+    const C: [[f32; 3]; 7] = [
+        [0.277_727_3, 0.005_407_344_6, 0.334_099_8],
+        [0.105_093_04, 1.404_613_5, 1.384_590_1],
+        [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+        [-4.634_230_4, -5.799_101, -19.332_44],
+        [6.228_27, 14.179_933, 56.690_55],
+        [4.776_385, -13.745_145, -65.353_03],
+        [-5.435_456, 4.645_852_6, 26.312_435],
+    ];
+    std::array::from_fn(|i| C.iter().rev().fold(0.0, |acc, c| acc * t + c[i]))
+}
+
+/// diverging colormap (blue, gray, red). `t` in [0,1] (clamped)
+pub fn coolwarm(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0);
+    let cool = [0.23, 0.299, 0.754];
+    let mid = [0.865, 0.865, 0.865];
+    let warm = [0.706, 0.016, 0.15];
+    let (a, b, s) = if t < 0.5 {
+        (cool, mid, 2.0 * t)
+    } else {
+        (mid, warm, 2.0 * t - 1.0)
+    };
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * s)
+}
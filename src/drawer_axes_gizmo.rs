@@ -0,0 +1,186 @@
+//! draw the XYZ axes in a corner of the viewport following the rotation of the camera
+//!
+//! The depth of the scene is kept. The gizmo is drawn into the front end of the depth range,
+//! so it is over the scene and the meshes drawn after it are occluded only by its lines.
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform float lineWidth;
+
+layout (location = 0) in vec3 axis;
+layout (location = 1) in vec2 corner; // (side in {-1,1}, end in {0,1})
+out vec3 v_color;
+
+void main() {
+    // rotation of the view without the scaling so that the axes have the unit length
+    mat3 m = mat3(frameMatView);
+    mat3 matRot = mat3(normalize(m[0]), normalize(m[1]), normalize(m[2]));
+    vec3 d = matRot * axis;
+    vec2 perp = length(d.xy) > 1.0e-5 ? normalize(vec2(-d.y, d.x)) : vec2(1.0, 0.0);
+    vec3 p = 0.8 * corner.y * d;
+    gl_Position = vec4(p.xy + perp * corner.x * lineWidth, -0.5 * p.z, 1.0);
+    v_color = axis;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+in vec3 v_color;
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(v_color, 1.0);
+}
+"#;
+
+/// the far end of the depth range of the gizmo. The scene farther than this is behind the gizmo
+const GIZMO_DEPTH_RANGE: f64 = 1.0e-3;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbo: Option<glow::NativeBuffer>,
+    /// width and height of the square region of the gizmo in points (i.e., logical pixels of egui)
+    pub size: i32,
+    /// distance from the bottom-left corner of the viewport in points
+    pub margin: i32,
+    /// width of the axis lines in points
+    pub line_width: f32,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            vertex_array: None,
+            vbo: None,
+            size: 80,
+            margin: 10,
+            line_width: 3.0,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let mut vtx2data = Vec::<f32>::with_capacity(3 * 6 * 5);
+        for axis in [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]] {
+            for corner in [
+                [-1., 0.],
+                [1., 0.],
+                [1., 1.],
+                [-1., 0.],
+                [1., 1.],
+                [-1., 1.],
+            ] {
+                vtx2data.extend_from_slice(&axis);
+                vtx2data.extend_from_slice(&corner);
+            }
+        }
+        unsafe {
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            let vao0 = gl.create_vertex_array().unwrap();
+            gl.bind_vertex_array(Some(vao0));
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&vtx2data),
+                glow::STATIC_DRAW,
+            );
+            let stride = (5 * std::mem::size_of::<f32>()) as i32;
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(1);
+            gl.vertex_attrib_pointer_f32(
+                1,
+                2,
+                glow::FLOAT,
+                false,
+                stride,
+                (3 * std::mem::size_of::<f32>()) as i32,
+            );
+            gl.bind_vertex_array(None);
+            self.vertex_array = Some(vao0);
+            self.vbo = Some(vbo);
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// draw the gizmo using the rotation part of the view of [`crate::frame_uniforms`].
+    /// `pixels_per_point` is the scale factor of the display (e.g., `egui::Context::pixels_per_point`)
+    pub fn draw(&self, gl: &glow::Context, pixels_per_point: f32) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            let mut viewport = [0i32; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+            let is_scissor = gl.is_enabled(glow::SCISSOR_TEST);
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            let depth_func = gl.get_parameter_i32(glow::DEPTH_FUNC) as u32;
+            let mut depth_range = [0f32; 2];
+            gl.get_parameter_f32_slice(glow::DEPTH_RANGE, &mut depth_range);
+            let mut scissor = [0i32; 4];
+            gl.get_parameter_i32_slice(glow::SCISSOR_BOX, &mut scissor);
+            //
+            let to_pixel = |v: i32| (v as f32 * pixels_per_point).round() as i32;
+            let (size, margin) = (to_pixel(self.size), to_pixel(self.margin));
+            let (x, y) = (viewport[0] + margin, viewport[1] + margin);
+            gl.viewport(x, y, size, size);
+            gl.enable(glow::SCISSOR_TEST);
+            gl.scissor(x, y, size, size);
+            // in front of the scene without clearing its depth
+            gl.depth_range(0.0, GIZMO_DEPTH_RANGE);
+            gl.depth_func(glow::LESS);
+            gl.enable(glow::DEPTH_TEST);
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            program
+                .set_f32(gl, "lineWidth", &[self.line_width / self.size as f32])
+                .unwrap();
+            gl.draw_arrays(glow::TRIANGLES, 0, 18);
+            gl.bind_vertex_array(None);
+            //
+            gl.viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+            gl.scissor(scissor[0], scissor[1], scissor[2], scissor[3]);
+            gl.depth_range(depth_range[0] as f64, depth_range[1] as f64);
+            gl.depth_func(depth_func);
+            if !is_scissor {
+                gl.disable(glow::SCISSOR_TEST);
+            }
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            self.program.as_ref().unwrap().destroy(gl);
+            gl.delete_vertex_array(self.vertex_array.unwrap());
+            gl.delete_buffer(self.vbo.unwrap());
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,70 @@
+//! draw wireframes of axis-aligned and oriented boxes by transforming the unit cube
+
+pub struct Drawer {
+    pub drawer_cube: crate::drawer_elem2vtx_vtx2xyz::Drawer,
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        let drawer_cube = crate::drawer_elem2vtx_vtx2xyz::Drawer::new();
+        Self { drawer_cube }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_cube.compile_shader(gl, builder);
+        let (edge2vtx, vtx2xyz) = crate::trimesh3_primitive::unit_cube_edges();
+        self.drawer_cube
+            .add_elem2vtx(gl, glow::LINES, &edge2vtx, [0., 0., 0.])
+            .unwrap();
+        self.drawer_cube.set_vtx2xyz(gl, &vtx2xyz, 3).unwrap();
+    }
+
+    pub fn set_color(&mut self, rgb: &[f32; 3]) {
+        self.drawer_cube.set_color(0, rgb);
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        self.drawer_cube.destroy(gl);
+    }
+
+    /// draw the image of the unit cube `[0,1]^3` by the affine `transform`
+    pub fn draw_transformed_unit_cube(&self, gl: &glow::Context, transform: &[f32; 16]) {
+        self.drawer_cube.draw_transformed(gl, transform);
+    }
+
+    /// draw the axis-aligned box `aabb = [min_x, min_y, min_z, max_x, max_y, max_z]`
+    pub fn draw_aabb3(&self, gl: &glow::Context, aabb: &[f32; 6]) {
+        #[rustfmt::skip]
+        let t = [
+            aabb[3] - aabb[0], 0., 0., 0.,
+            0., aabb[4] - aabb[1], 0., 0.,
+            0., 0., aabb[5] - aabb[2], 0.,
+            aabb[0], aabb[1], aabb[2], 1.,
+        ];
+        self.draw_transformed_unit_cube(gl, &t);
+    }
+
+    /// draw the oriented box `obb = [center, half_axis_u, half_axis_v, half_axis_w]`
+    /// (the format of `del_geo_core::obb3`)
+    pub fn draw_obb3(&self, gl: &glow::Context, obb: &[f32; 12]) {
+        let (c, u, v, w) = (&obb[0..3], &obb[3..6], &obb[6..9], &obb[9..12]);
+        #[rustfmt::skip]
+        let t = [
+            2. * u[0], 2. * u[1], 2. * u[2], 0.,
+            2. * v[0], 2. * v[1], 2. * v[2], 0.,
+            2. * w[0], 2. * w[1], 2. * w[2], 0.,
+            c[0] - u[0] - v[0] - w[0], c[1] - u[1] - v[1] - w[1], c[2] - u[2] - v[2] - w[2], 1.,
+        ];
+        self.draw_transformed_unit_cube(gl, &t);
+    }
+}
@@ -0,0 +1,639 @@
+//! debug overlays of a mesh drawn from its uploaded vertex buffers (no geometry is re-uploaded)
+//!
+//! * the vertex normals, the vertex tangents or the face normals as the line segments
+//! * the face centroids as the points, and their indices as the labels
+//! * the front faces and the back faces in different colors to reveal inconsistent winding
+//!
+//! The buffers are given as [`AttribView`] (e.g., from [`crate::drawer_mesh::Drawer::attrib_view`]
+//! or [`crate::drawer_elem2vtx_vtx2xyz::Drawer::attrib_view_xyz`]).
+//! The face normals and centroids are drawn either from the triangles without the indices
+//! (e.g., [`crate::drawer_polygon_mesh::Drawer::drawer_fill`]) or from the indexed triangles.
+//! For the latter, the positions are copied on the GPU into a texture read by the index, and
+//! only the indices are uploaded. Set the views again if the vertices change.
+//!
+//! [`AttribView`]: crate::vertex_layout::AttribView
+
+use crate::vertex_layout::AttribView;
+use glow::HasContext;
+
+pub const VS_SEGMENT: &str = r#"
+#include "frame_uniforms"
+uniform float normalLength;
+uniform float pointSize;
+uniform bool isFace;
+uniform bool isIndexed;
+uniform highp sampler2D vtx2xyzTex; // the vertex buffer with one float per texel
+uniform int vtxStride; // in floats
+uniform int vtxOffset; // in floats
+in vec3 p0In;
+in vec3 p1In;
+in vec3 p2In;
+in vec3 nrmIn;
+in uvec3 tri2vtxIn;
+
+vec3 fetch_xyz(uint i_vtx) {
+    int width = textureSize(vtx2xyzTex, 0).x;
+    vec3 p;
+    for(int i = 0; i < 3; ++i){
+        int j = int(i_vtx) * vtxStride + vtxOffset + i;
+        p[i] = texelFetch(vtx2xyzTex, ivec2(j % width, j / width), 0).r;
+    }
+    return p;
+}
+
+void main() {
+    vec3 org = p0In;
+    vec3 dir = nrmIn;
+    if( isFace ){
+        vec3 p0 = p0In;
+        vec3 p1 = p1In;
+        vec3 p2 = p2In;
+        if( isIndexed ){
+            p0 = fetch_xyz(tri2vtxIn.x);
+            p1 = fetch_xyz(tri2vtxIn.y);
+            p2 = fetch_xyz(tri2vtxIn.z);
+        }
+        org = (p0 + p1 + p2) / 3.0;
+        dir = cross(p1 - p0, p2 - p0);
+    }
+    // the line segment from the origin (vertex 0) to the tip (vertex 1)
+    vec3 p = org + (gl_VertexID == 1 ? normalLength * normalize(dir) : vec3(0.0));
+    gl_Position = frameMatPrj * frameMatView * vec4(p, 1.0);
+    gl_PointSize = pointSize;
+}
+"#;
+
+pub const VS_ORIENTATION: &str = r#"
+#include "frame_uniforms"
+in vec3 p0In;
+
+void main() {
+    gl_Position = frameMatPrj * frameMatView * vec4(p0In, 1.0);
+}
+"#;
+
+pub const FS_SEGMENT: &str = r#"
+uniform vec3 color;
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+pub const FS_ORIENTATION: &str = r#"
+uniform vec3 frontColor;
+uniform vec3 backColor;
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(gl_FrontFacing ? frontColor : backColor, 1.0);
+}
+"#;
+
+pub struct Drawer {
+    program_segment: Option<crate::shader_program::ShaderProgram>,
+    program_orientation: Option<crate::shader_program::ShaderProgram>,
+    vao_vertex: Option<glow::NativeVertexArray>,
+    vao_tangent: Option<glow::NativeVertexArray>,
+    vao_face: Option<glow::NativeVertexArray>,
+    vao_orientation: Option<glow::NativeVertexArray>,
+    num_vtx: usize,
+    num_vtx_tangent: usize,
+    num_face: usize,
+    /// the indices of the indexed triangles
+    vbo_tri2vtx: Option<glow::NativeBuffer>,
+    /// the positions of the indexed triangles, their stride and offset in floats
+    tex_vtx2xyz: Option<(glow::NativeTexture, usize, usize)>,
+    num_vtx_orientation: usize,
+    /// the element buffer of the orientation pass, its index type and the number of the indices
+    elem2vtx: Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)>,
+    /// length of the normals in the world coordinate
+    pub normal_length: f32,
+    pub vertex_normal_color: [f32; 3],
+    pub tangent_color: [f32; 3],
+    pub face_normal_color: [f32; 3],
+    pub centroid_color: [f32; 3],
+    /// size in pixels of the centroids
+    pub centroid_size: f32,
+    pub front_color: [f32; 3],
+    pub back_color: [f32; 3],
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program_segment: None,
+            program_orientation: None,
+            vao_vertex: None,
+            vao_tangent: None,
+            vao_face: None,
+            vao_orientation: None,
+            num_vtx: 0,
+            num_vtx_tangent: 0,
+            num_face: 0,
+            vbo_tri2vtx: None,
+            tex_vtx2xyz: None,
+            num_vtx_orientation: 0,
+            elem2vtx: None,
+            normal_length: 0.1,
+            vertex_normal_color: [0.0, 0.0, 1.0],
+            tangent_color: [1.0, 0.0, 0.0],
+            face_normal_color: [0.0, 0.6, 0.0],
+            centroid_color: [0.0, 0.0, 0.0],
+            centroid_size: 4.0,
+            front_color: [0.6, 0.6, 0.9],
+            back_color: [0.9, 0.2, 0.2],
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program_segment = builder.build(gl, VS_SEGMENT, FS_SEGMENT, &[]).unwrap();
+        self.set_program_segment(gl, program_segment);
+        let program_orientation = builder
+            .build(gl, VS_ORIENTATION, FS_ORIENTATION, &[])
+            .unwrap();
+        self.set_program_orientation(gl, program_orientation);
+        unsafe {
+            self.vao_vertex = Some(gl.create_vertex_array().unwrap());
+            self.vao_tangent = Some(gl.create_vertex_array().unwrap());
+            self.vao_face = Some(gl.create_vertex_array().unwrap());
+            self.vao_orientation = Some(gl.create_vertex_array().unwrap());
+        }
+    }
+
+    /// replace the program of the normals and the centroids (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_SEGMENT`] and [`FS_SEGMENT`]). The previous program is released
+    pub fn set_program_segment(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program_segment.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// replace the program of the orientation (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_ORIENTATION`] and [`FS_ORIENTATION`]).
+    /// The previous program is released
+    pub fn set_program_orientation(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program_orientation.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// the vertex positions and the vertex normals for [`Drawer::draw_vertex_normals`]
+    pub fn set_vertex_normals(
+        &mut self,
+        gl: &glow::Context,
+        xyz: &AttribView,
+        nrm: &AttribView,
+    ) -> Result<(), String> {
+        check_vec3("xyz", xyz)?;
+        check_vec3("nrm", nrm)?;
+        check_num_vtx("nrm", nrm, xyz.num_vtx)?;
+        let program = compiled(&self.program_segment)?;
+        self.num_vtx = xyz.num_vtx;
+        unsafe {
+            gl.bind_vertex_array(self.vao_vertex);
+            bind_attrib(gl, program, "p0In", xyz, 0, 1);
+            bind_attrib(gl, program, "nrmIn", nrm, 0, 1);
+            gl.bind_vertex_array(None);
+        }
+        Ok(())
+    }
+
+    /// the vertex positions and the vertex tangents for [`Drawer::draw_vertex_tangents`].
+    /// The tangents have three components, or four with the handedness in the last one
+    pub fn set_vertex_tangents(
+        &mut self,
+        gl: &glow::Context,
+        xyz: &AttribView,
+        tan: &AttribView,
+    ) -> Result<(), String> {
+        check_vec3("xyz", xyz)?;
+        if !(3..=4).contains(&tan.num_comp) || tan.atype != crate::vertex_layout::AttribType::F32 {
+            return Err("tan: three or four f32 components are expected".to_string());
+        }
+        check_num_vtx("tan", tan, xyz.num_vtx)?;
+        let program = compiled(&self.program_segment)?;
+        self.num_vtx_tangent = xyz.num_vtx;
+        unsafe {
+            gl.bind_vertex_array(self.vao_tangent);
+            bind_attrib(gl, program, "p0In", xyz, 0, 1);
+            bind_attrib(gl, program, "nrmIn", tan, 0, 1);
+            gl.bind_vertex_array(None);
+        }
+        Ok(())
+    }
+
+    /// the vertex positions of the triangles without the indices for
+    /// [`Drawer::draw_face_normals`] and [`Drawer::draw_face_centroids`]
+    pub fn set_triangles(&mut self, gl: &glow::Context, xyz: &AttribView) -> Result<(), String> {
+        check_vec3("xyz", xyz)?;
+        crate::validate::multiple_of("triangles", xyz.num_vtx, 3)?;
+        compiled(&self.program_segment)?;
+        self.delete_indexed_triangles(gl);
+        let program = compiled(&self.program_segment)?;
+        self.num_face = xyz.num_vtx / 3;
+        unsafe {
+            gl.bind_vertex_array(self.vao_face);
+            for (i_node, name) in ["p0In", "p1In", "p2In"].iter().enumerate() {
+                // each instance advances by the three vertices
+                bind_attrib(gl, program, name, xyz, i_node, 3);
+            }
+            if let Some(location) = program.attrib_location("tri2vtxIn") {
+                gl.disable_vertex_attrib_array(location);
+            }
+            gl.bind_vertex_array(None);
+        }
+        Ok(())
+    }
+
+    /// the vertex positions and the indices of the triangles (e.g., the ones given to
+    /// [`crate::drawer_elem2vtx_vtx2xyz::Drawer::add_elem2vtx`]) for
+    /// [`Drawer::draw_face_normals`] and [`Drawer::draw_face_centroids`].
+    /// Error if an index is out of the range of the vertices
+    pub fn set_indexed_triangles<T>(
+        &mut self,
+        gl: &glow::Context,
+        xyz: &AttribView,
+        tri2vtx: &[T],
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<usize>,
+    {
+        check_vec3("xyz", xyz)?;
+        crate::validate::multiple_of("tri2vtx", tri2vtx.len(), 3)?;
+        crate::validate::index_range("tri2vtx", tri2vtx, xyz.num_vtx)?;
+        let f32_size = std::mem::size_of::<f32>();
+        if !xyz.stride.is_multiple_of(f32_size) || !xyz.offset.is_multiple_of(f32_size) {
+            return Err("xyz: the stride and the offset must be multiples of four bytes".into());
+        }
+        compiled(&self.program_segment)?;
+        self.delete_indexed_triangles(gl);
+        let program = compiled(&self.program_segment)?;
+        let tri2vtx: Vec<u32> = tri2vtx.iter().map(|&i_vtx| i_vtx.as_() as u32).collect();
+        self.num_face = tri2vtx.len() / 3;
+        unsafe {
+            let tex = copy_to_texture(gl, xyz);
+            self.tex_vtx2xyz = Some((tex, xyz.stride / f32_size, xyz.offset / f32_size));
+            gl.bind_vertex_array(self.vao_face);
+            for name in ["p0In", "p1In", "p2In"] {
+                if let Some(location) = program.attrib_location(name) {
+                    gl.disable_vertex_attrib_array(location);
+                }
+            }
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&tri2vtx),
+                glow::STATIC_DRAW,
+            );
+            if let Some(location) = program.attrib_location("tri2vtxIn") {
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_i32(location, 3, glow::UNSIGNED_INT, 0, 0);
+                gl.vertex_attrib_divisor(location, 1);
+            }
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_vertex_array(None);
+            self.vbo_tri2vtx = Some(vbo);
+        }
+        Ok(())
+    }
+
+    fn delete_indexed_triangles(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(vbo) = self.vbo_tri2vtx.take() {
+                gl.delete_buffer(vbo);
+            }
+            if let Some((tex, _, _)) = self.tex_vtx2xyz.take() {
+                gl.delete_texture(tex);
+            }
+        }
+    }
+
+    /// the vertex positions and the optional element buffer (e.g., from
+    /// [`crate::drawer_mesh::Drawer::element_buffer`]) of the triangles for
+    /// [`Drawer::draw_orientation`]
+    pub fn set_orientation_mesh(
+        &mut self,
+        gl: &glow::Context,
+        xyz: &AttribView,
+        elem2vtx: Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)>,
+    ) -> Result<(), String> {
+        check_vec3("xyz", xyz)?;
+        let program = compiled(&self.program_orientation)?;
+        self.num_vtx_orientation = xyz.num_vtx;
+        unsafe {
+            gl.bind_vertex_array(self.vao_orientation);
+            bind_attrib(gl, program, "p0In", xyz, 0, 0);
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, elem2vtx.map(|e| e.0));
+            gl.bind_vertex_array(None);
+        }
+        self.elem2vtx = elem2vtx;
+        Ok(())
+    }
+
+    /// add the index of each triangle of `tri2vtx` at its centroid to the labels
+    pub fn add_face_labels(
+        drawer_text: &mut crate::drawer_text::Drawer,
+        tri2vtx: &[usize],
+        vtx2xyz: &[f32],
+        rgb: [f32; 3],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("tri2vtx", tri2vtx.len(), 3)?;
+        crate::validate::index_range("tri2vtx", tri2vtx, vtx2xyz.len() / 3)?;
+        for (i_tri, node2vtx) in tri2vtx.chunks(3).enumerate() {
+            let mut c = [0f32; 3];
+            for &i_vtx in node2vtx {
+                c = del_geo_core::vec3::add(&c, arrayref::array_ref![vtx2xyz, i_vtx * 3, 3]);
+            }
+            let c = del_geo_core::vec3::scale(&c, 1.0 / 3.0);
+            drawer_text.add_label(
+                crate::drawer_text::Anchor::World(c),
+                &i_tri.to_string(),
+                rgb,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn draw_vertex_normals(&self, gl: &glow::Context) {
+        self.draw_segments(
+            gl,
+            Segment::VertexNormal,
+            glow::LINES,
+            self.vertex_normal_color,
+        );
+    }
+
+    pub fn draw_vertex_tangents(&self, gl: &glow::Context) {
+        self.draw_segments(gl, Segment::VertexTangent, glow::LINES, self.tangent_color);
+    }
+
+    pub fn draw_face_normals(&self, gl: &glow::Context) {
+        self.draw_segments(gl, Segment::Face, glow::LINES, self.face_normal_color);
+    }
+
+    pub fn draw_face_centroids(&self, gl: &glow::Context) {
+        self.draw_segments(gl, Segment::Face, glow::POINTS, self.centroid_color);
+    }
+
+    fn draw_segments(&self, gl: &glow::Context, segment: Segment, mode: u32, color: [f32; 3]) {
+        let Some(program) = &self.program_segment else {
+            return;
+        };
+        let (vao, num_instance) = match segment {
+            Segment::VertexNormal => (self.vao_vertex, self.num_vtx),
+            Segment::VertexTangent => (self.vao_tangent, self.num_vtx_tangent),
+            Segment::Face => (self.vao_face, self.num_face),
+        };
+        let is_face = segment == Segment::Face;
+        if num_instance == 0 {
+            return;
+        }
+        unsafe {
+            gl.use_program(Some(program.program));
+            program
+                .set_f32(gl, "normalLength", &[self.normal_length])
+                .unwrap();
+            program
+                .set_f32(gl, "pointSize", &[self.centroid_size])
+                .unwrap();
+            program.set_bool(gl, "isFace", is_face).unwrap();
+            program.set_vec3(gl, "color", &color).unwrap();
+            let tex_vtx2xyz = self.tex_vtx2xyz.filter(|_| is_face);
+            program
+                .set_bool(gl, "isIndexed", tex_vtx2xyz.is_some())
+                .unwrap();
+            let prev_active_texture = gl.get_parameter_i32(glow::ACTIVE_TEXTURE) as u32;
+            gl.active_texture(glow::TEXTURE0);
+            let prev_texture = gl.get_parameter_texture(glow::TEXTURE_BINDING_2D);
+            if let Some((tex, stride, offset)) = tex_vtx2xyz {
+                gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+                program.set_i32(gl, "vtx2xyzTex", 0).unwrap();
+                program.set_i32(gl, "vtxStride", stride as i32).unwrap();
+                program.set_i32(gl, "vtxOffset", offset as i32).unwrap();
+            }
+            gl.enable(glow::PROGRAM_POINT_SIZE);
+            gl.bind_vertex_array(vao);
+            // the points are drawn only at the origins of the segments
+            let count = if mode == glow::POINTS { 1 } else { 2 };
+            gl.draw_arrays_instanced(mode, 0, count, num_instance as i32);
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, prev_texture);
+            gl.active_texture(prev_active_texture);
+        }
+    }
+
+    /// draw the front faces in `front_color` and the back faces in `back_color`
+    pub fn draw_orientation(&self, gl: &glow::Context) {
+        let Some(program) = &self.program_orientation else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            program
+                .set_vec3(gl, "frontColor", &self.front_color)
+                .unwrap();
+            program.set_vec3(gl, "backColor", &self.back_color).unwrap();
+            let is_cull_face = gl.is_enabled(glow::CULL_FACE);
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            gl.disable(glow::CULL_FACE);
+            gl.enable(glow::DEPTH_TEST);
+            // push the faces behind the lines drawn on them
+            gl.enable(glow::POLYGON_OFFSET_FILL);
+            gl.polygon_offset(1.0, 1.0);
+            gl.bind_vertex_array(self.vao_orientation);
+            match self.elem2vtx {
+                Some((_, index_type, num_index)) => {
+                    gl.draw_elements(glow::TRIANGLES, num_index as i32, index_type.gl_type(), 0)
+                }
+                None => gl.draw_arrays(glow::TRIANGLES, 0, self.num_vtx_orientation as i32),
+            }
+            gl.bind_vertex_array(None);
+            gl.disable(glow::POLYGON_OFFSET_FILL);
+            if is_cull_face {
+                gl.enable(glow::CULL_FACE);
+            }
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// the vertex buffers are owned by the mesh drawers and not deleted
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.delete_indexed_triangles(gl);
+        unsafe {
+            for program in [self.program_segment.take(), self.program_orientation.take()]
+                .into_iter()
+                .flatten()
+            {
+                program.destroy(gl);
+            }
+            for vao in [
+                self.vao_vertex.take(),
+                self.vao_tangent.take(),
+                self.vao_face.take(),
+                self.vao_orientation.take(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                gl.delete_vertex_array(vao);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    VertexNormal,
+    VertexTangent,
+    Face,
+}
+
+fn check_vec3(name: &str, view: &AttribView) -> Result<(), String> {
+    if view.num_comp != 3 || view.atype != crate::vertex_layout::AttribType::F32 {
+        return Err(format!("{name}: three f32 components are expected"));
+    }
+    Ok(())
+}
+
+fn check_num_vtx(name: &str, view: &AttribView, num_vtx: usize) -> Result<(), String> {
+    if view.num_vtx != num_vtx {
+        return Err(format!(
+            "{name}: {} vertices but {num_vtx} of xyz are expected",
+            view.num_vtx
+        ));
+    }
+    Ok(())
+}
+
+/// the program, or an error before [`Drawer::compile_shader`]
+fn compiled(
+    program: &Option<crate::shader_program::ShaderProgram>,
+) -> Result<&crate::shader_program::ShaderProgram, String> {
+    program
+        .as_ref()
+        .ok_or_else(|| "debug overlay: the shader is not compiled yet".to_string())
+}
+
+/// bind the `i_node`-th of the `num_node` consecutive vertices of `view` to the attribute.
+/// `num_node` is zero for the per-vertex attribute, otherwise the attribute is per instance
+unsafe fn bind_attrib(
+    gl: &glow::Context,
+    program: &crate::shader_program::ShaderProgram,
+    name: &str,
+    view: &AttribView,
+    i_node: usize,
+    num_node: usize,
+) {
+    let Some(location) = program.attrib_location(name) else {
+        return; // optimized out
+    };
+    gl.bind_buffer(glow::ARRAY_BUFFER, Some(view.vbo));
+    gl.enable_vertex_attrib_array(location);
+    gl.vertex_attrib_pointer_f32(
+        location,
+        3,
+        glow::FLOAT,
+        false,
+        (view.stride * num_node.max(1)) as i32,
+        (view.offset + view.stride * i_node) as i32,
+    );
+    gl.vertex_attrib_divisor(location, num_node.min(1) as u32);
+    gl.bind_buffer(glow::ARRAY_BUFFER, None);
+}
+
+/// width of the texture holding the vertex buffer, below the minimum `GL_MAX_TEXTURE_SIZE` of GLES 3.0
+const TEX_WIDTH: usize = 2048;
+
+/// copy the floats of the vertices of `view` from its buffer into a `R32F` texture of
+/// [`TEX_WIDTH`] floats per row on the GPU
+unsafe fn copy_to_texture(gl: &glow::Context, view: &AttribView) -> glow::NativeTexture {
+    let num_float = view.stride / std::mem::size_of::<f32>() * view.num_vtx;
+    let width = num_float.clamp(1, TEX_WIDTH);
+    let num_row_full = num_float / width;
+    let num_float_last = num_float % width;
+    let height = num_row_full + usize::from(num_float_last != 0);
+    let tex = gl.create_texture().unwrap();
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        glow::R32F as i32,
+        width as i32,
+        height.max(1) as i32,
+        0,
+        glow::RED,
+        glow::FLOAT,
+        glow::PixelUnpackData::Slice(None),
+    );
+    for param in [glow::TEXTURE_MIN_FILTER, glow::TEXTURE_MAG_FILTER] {
+        gl.tex_parameter_i32(glow::TEXTURE_2D, param, glow::NEAREST as i32);
+    }
+    gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, Some(view.vbo));
+    // the full rows, then the partial last row so that nothing is read beyond the vertices
+    for (y, w, h) in [(0, width, num_row_full), (num_row_full, num_float_last, 1)] {
+        if w == 0 || h == 0 {
+            continue;
+        }
+        gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            y as i32,
+            w as i32,
+            h as i32,
+            glow::RED,
+            glow::FLOAT,
+            glow::PixelUnpackData::BufferOffset((y * width * std::mem::size_of::<f32>()) as u32),
+        );
+    }
+    gl.bind_buffer(glow::PIXEL_UNPACK_BUFFER, None);
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    tex
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_view() {
+        let view = |num_comp: usize, num_vtx: usize| AttribView {
+            vbo: glow::NativeBuffer(std::num::NonZeroU32::new(1).unwrap()),
+            num_comp,
+            atype: crate::vertex_layout::AttribType::F32,
+            stride: num_comp * 4,
+            offset: 0,
+            num_vtx,
+        };
+        assert!(check_vec3("xyz", &view(3, 4)).is_ok());
+        assert!(check_vec3("xyz", &view(2, 4)).is_err());
+        // the normals of another mesh
+        assert!(check_num_vtx("nrm", &view(3, 4), 4).is_ok());
+        assert!(check_num_vtx("nrm", &view(3, 5), 4).is_err());
+        assert!(compiled(&None).is_err());
+    }
+}
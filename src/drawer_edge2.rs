@@ -14,13 +14,18 @@ impl Drawer {
         Self { drawer_quad }
     }
 
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        self.drawer_quad.compile_shader(gl);
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_quad.compile_shader(gl, builder);
         let vtx2xy = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
         let tri2vtx = vec![0, 1, 2, 0, 2, 3];
         self.drawer_quad
-            .add_elem2vtx(gl, glow::TRIANGLES, &tri2vtx, [0., 0., 0.]);
-        self.drawer_quad.set_vtx2xyz(gl, &vtx2xy, 2);
+            .add_elem2vtx(gl, glow::TRIANGLES, &tri2vtx, [0., 0., 0.])
+            .unwrap();
+        self.drawer_quad.set_vtx2xyz(gl, &vtx2xy, 2).unwrap();
     }
 
     pub fn set_color(&mut self, rgb: &[f32; 3]) {
@@ -31,14 +36,7 @@ impl Drawer {
         self.drawer_quad.destroy(gl);
     }
 
-    pub fn draw_edge2(
-        &self,
-        gl: &glow::Context,
-        mvp: &[f32; 16],
-        ps: &[f32; 2],
-        pe: &[f32; 2],
-        width_ndc: f32,
-    ) {
+    pub fn draw_edge2(&self, gl: &glow::Context, ps: &[f32; 2], pe: &[f32; 2], width_ndc: f32) {
         use del_geo_core::vec2::Vec2;
         let v_se = pe.sub(ps);
         let u_se = v_se.normalize().rot90().scale(width_ndc);
@@ -49,22 +47,16 @@ impl Drawer {
             &left_bottom_corner_world,
         );
         let m = del_geo_core::mat4_col_major::from_mat3_col_major_adding_z(&m);
-        self.drawer_quad.draw(gl, &m, mvp);
+        self.drawer_quad.draw_transformed(gl, &m);
     }
 
-    pub fn draw_polyloop2(
-        &self,
-        gl: &glow::Context,
-        mvp: &[f32; 16],
-        vtx2xy: &[f32],
-        width_ndc: f32,
-    ) {
+    pub fn draw_polyloop2(&self, gl: &glow::Context, vtx2xy: &[f32], width_ndc: f32) {
         let num_vtx = vtx2xy.len() / 2;
         for i0_vtx in 0..num_vtx {
             let i1_vtx = (i0_vtx + 1) % num_vtx;
             let p0 = arrayref::array_ref![vtx2xy, i0_vtx * 2, 2];
             let p1 = arrayref::array_ref![vtx2xy, i1_vtx * 2, 2];
-            self.draw_edge2(gl, mvp, p0, p1, width_ndc);
+            self.draw_edge2(gl, p0, p1, width_ndc);
         }
     }
 }
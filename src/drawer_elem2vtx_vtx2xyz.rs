@@ -2,22 +2,62 @@
 
 use glow::HasContext;
 
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform mat4 matLocal; // transformation of the sub-mesh applied before the view
+uniform bool isZFlip;
+layout (location = 0) in vec3 position;
+out vec3 posView;
+out vec3 posModel;
+
+void main() {
+    vec4 p = frameMatView * matLocal * vec4(position, 1.0);
+    gl_Position = frame_project(p, isZFlip);
+    posView = p.xyz;
+    posModel = position;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "normal_output"
+uniform vec3 color;
+uniform vec4 clipPlane; // the side `dot(clipPlane, vec4(p, 1)) > 0` is discarded
+in vec3 posView;
+in vec3 posModel;
+layout (location = 0) out vec4 FragColor;
+
+void main() {
+    if( dot(clipPlane, vec4(posModel, 1.0)) > 0.0 ){ discard; }
+    FragColor = vec4(color, 1.0);
+    write_normal(flat_normal(posView));
+}
+"#;
+
 struct ElementBufferObject {
+    /// name of the sub-mesh (empty for the one added by `add_elem2vtx`)
+    name: String,
     mode: u32,
     elem_size: usize,
+    index_type: crate::index_buffer::IndexType,
+    max_index: Option<u32>,
     ebo: Option<glow::NativeBuffer>,
     color: [f32; 3],
+    is_visible: bool,
+    /// transformation applied before the view matrix
+    mat_local: Option<[f32; 16]>,
 }
 
 pub struct Drawer {
-    program: Option<glow::NativeProgram>,
+    program: Option<crate::shader_program::ShaderProgram>,
     pub ndim: usize,
+    /// plane `[a, b, c, d]` in the coordinate of `vtx2xyz` whose side `a*x + b*y + c*z + d > 0`
+    /// is discarded. `None` for no clipping
+    pub clip_plane: Option<[f32; 4]>,
+    /// flip z of the projection in [`Drawer::draw`] (see [`crate::frame_uniforms::FrameUniforms::is_z_flip`])
+    pub is_z_flip: bool,
     num_point: usize,
     vertex_array: Option<glow::NativeVertexArray>,
-    // uniform variables
-    loc_color: Option<glow::NativeUniformLocation>,
-    loc_mat_modelview: Option<glow::NativeUniformLocation>,
-    loc_mat_projection: Option<glow::NativeUniformLocation>,
+    vbo_xyz: Option<glow::NativeBuffer>,
     // elemenb buffer object
     ebos: Vec<ElementBufferObject>,
 }
@@ -27,97 +67,204 @@ impl Drawer {
         Drawer {
             program: None,
             ndim: 0,
+            clip_plane: None,
+            is_z_flip: false,
             num_point: 0,
             vertex_array: None,
-            loc_color: None, // -1 is the failure flag
-            loc_mat_modelview: None,
-            loc_mat_projection: None,
+            vbo_xyz: None,
             ebos: Vec::<ElementBufferObject>::new(),
         }
     }
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-
-        let vs_src = r#"
-uniform mat4 matMV;
-uniform mat4 matPrj;
-in vec3 position;
-
-void main() {
-    gl_Position = matPrj * matMV * vec4(position, 1.0);
-    // gl_Position = vec4(position, 1.0);
-}
-"#;
-
-        let fs_src = r#"
-uniform vec3 color;
-out vec4 FragColor;
-
-void main() {
-    FragColor = vec4(color, 1.0);
-}
-"#;
-
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        self.set_program(gl, program);
         unsafe {
-            self.program = crate::compile_shaders(gl, shader_version, vs_src, fs_src);
-            self.loc_mat_modelview = gl.get_uniform_location(self.program.unwrap(), "matMV");
-            self.loc_mat_projection = gl.get_uniform_location(self.program.unwrap(), "matPrj");
-            self.loc_color = gl.get_uniform_location(self.program.unwrap(), "color");
             gl.use_program(None);
             let vao0 = gl.create_vertex_array().unwrap();
             self.vertex_array = Some(vao0);
         }
     }
 
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]).
+    /// The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// add the element buffer stored with the smallest index type
     pub fn add_elem2vtx<T>(
         &mut self,
         gl: &glow::Context,
         mode: u32,
         elem2vtx: &[T],
         color: [f32; 3],
-    ) where
-        T: 'static + Copy + num_traits::AsPrimitive<u32>,
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
     {
-        let elem2vtx0: Vec<u32> = elem2vtx.iter().map(|i| (*i).as_()).collect();
+        self.add_elem2vtx_with_index_type(gl, mode, elem2vtx, color, None)
+    }
+
+    /// add the element buffer stored with `index_type` (the smallest type if it is `None`).
+    /// Error if an index does not fit in `index_type` or is out of the range of the vertices
+    pub fn add_elem2vtx_with_index_type<T>(
+        &mut self,
+        gl: &glow::Context,
+        mode: u32,
+        elem2vtx: &[T],
+        color: [f32; 3],
+        index_type: Option<crate::index_buffer::IndexType>,
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
+    {
+        let index_data = crate::index_buffer::IndexData::new(elem2vtx, index_type)?;
+        self.push_ebo(gl, "", mode, &index_data, color)?;
+        Ok(())
+    }
+
+    /// add a named sub-mesh. If `vtx_range` is given, `elem2vtx` is relative to its start and
+    /// must not refer beyond its end (e.g., a part of an assembly whose vertices are
+    /// concatenated in `vtx2xyz`). Error if `vtx_range` ends beyond the vertices already set.
+    /// Returns the index of the sub-mesh
+    pub fn add_submesh<T>(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+        mode: u32,
+        elem2vtx: &[T],
+        color: [f32; 3],
+        vtx_range: Option<std::ops::Range<usize>>,
+    ) -> Result<usize, String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
+    {
+        let index_data = match vtx_range {
+            Some(vtx_range) => {
+                if self.num_point != 0 && vtx_range.end > self.num_point {
+                    return Err(format!(
+                        "{name}: the vertex range {vtx_range:?} is out of range for {} vertices",
+                        self.num_point
+                    ));
+                }
+                let elem2vtx: Vec<u64> = elem2vtx.iter().map(|i| (*i).as_()).collect();
+                crate::validate::index_range(name, &elem2vtx, vtx_range.len())?;
+                let offset = vtx_range.start as u64;
+                let elem2vtx: Vec<u64> = elem2vtx.iter().map(|i| i + offset).collect();
+                crate::index_buffer::IndexData::new(&elem2vtx, None)?
+            }
+            None => crate::index_buffer::IndexData::new(elem2vtx, None)?,
+        };
+        self.push_ebo(gl, name, mode, &index_data, color)?;
+        Ok(self.ebos.len() - 1)
+    }
+
+    fn push_ebo(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+        mode: u32,
+        index_data: &crate::index_buffer::IndexData,
+        color: [f32; 3],
+    ) -> Result<(), String> {
+        if self.num_point != 0 {
+            crate::validate::max_index("elem2vtx", index_data.max_index, self.num_point)?;
+        }
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
             let ebo0 = gl.create_buffer().unwrap();
             gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo0));
-            gl.buffer_data_u8_slice(
-                glow::ELEMENT_ARRAY_BUFFER,
-                bytemuck::cast_slice(&elem2vtx0),
-                glow::STATIC_DRAW,
-            );
+            index_data.upload(gl, glow::STATIC_DRAW);
             self.ebos.push(ElementBufferObject {
+                name: name.to_string(),
                 mode,
-                elem_size: elem2vtx0.len(),
+                elem_size: index_data.num_index,
+                index_type: index_data.index_type,
+                max_index: index_data.max_index,
                 ebo: Some(ebo0),
                 color,
+                is_visible: true,
+                mat_local: None,
             });
             gl.bind_vertex_array(None);
         }
+        Ok(())
+    }
+
+    /// delete all the element buffers
+    pub fn clear_elem2vtx(&mut self, gl: &glow::Context) {
+        for ebo in self.ebos.drain(..) {
+            if let Some(ebo) = ebo.ebo {
+                unsafe {
+                    gl.delete_buffer(ebo);
+                }
+            }
+        }
+    }
+
+    /// index of the first sub-mesh with the name
+    pub fn submesh_index(&self, name: &str) -> Option<usize> {
+        self.ebos.iter().position(|ebo| ebo.name == name)
     }
 
-    pub fn set_vtx2xyz(&mut self, gl: &glow::Context, vtx2xyz: &[f32], ndim: usize) {
+    pub fn num_submesh(&self) -> usize {
+        self.ebos.len()
+    }
+
+    pub fn set_visible(&mut self, i_ebo: usize, is_visible: bool) {
+        self.ebos[i_ebo].is_visible = is_visible;
+    }
+
+    /// local transformation of the sub-mesh applied before the view matrix.
+    /// `None` for the identity
+    pub fn set_transform(&mut self, i_ebo: usize, mat_local: Option<&[f32; 16]>) {
+        self.ebos[i_ebo].mat_local = mat_local.copied();
+    }
+
+    /// error if the length is not a multiple of `ndim`, the element buffers refer beyond it,
+    /// or (with [`crate::validate::IS_ENABLED`]) a coordinate is not finite
+    pub fn set_vtx2xyz(
+        &mut self,
+        gl: &glow::Context,
+        vtx2xyz: &[f32],
+        ndim: usize,
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), ndim)?;
+        crate::validate::finite("vtx2xyz", vtx2xyz)?;
+        let num_point = vtx2xyz.len() / ndim;
+        for ebo in &self.ebos {
+            crate::validate::max_index("elem2vtx", ebo.max_index, num_point)?;
+        }
         self.ndim = ndim;
-        self.num_point = vtx2xyz.len() / self.ndim;
+        self.num_point = num_point;
         unsafe {
             gl.use_program(None);
             gl.bind_vertex_array(self.vertex_array);
-            let vbo = gl.create_buffer().unwrap();
+            let vbo = *self
+                .vbo_xyz
+                .get_or_insert_with(|| gl.create_buffer().unwrap());
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
                 bytemuck::cast_slice(vtx2xyz),
                 glow::STATIC_DRAW,
             );
-            let pos_attrib = gl
-                .get_attrib_location(self.program.unwrap(), "position")
-                .unwrap();
+            let Some(pos_attrib) = self.program.as_ref().unwrap().attrib_location("position")
+            else {
+                // optimized out by the driver as it does not contribute to the output
+                gl.bind_vertex_array(None);
+                return Ok(());
+            };
             gl.enable_vertex_attrib_array(pos_attrib);
             gl.vertex_attrib_pointer_f32(
                 pos_attrib,
@@ -129,49 +276,83 @@ void main() {
             );
             gl.bind_vertex_array(None);
         }
+        Ok(())
+    }
+
+    /// the uploaded vertex positions (e.g., for [`crate::drawer_debug_overlay`]).
+    /// `None` before the positions are set
+    pub fn attrib_view_xyz(&self) -> Option<crate::vertex_layout::AttribView> {
+        Some(crate::vertex_layout::AttribView {
+            vbo: self.vbo_xyz?,
+            num_comp: self.ndim,
+            atype: crate::vertex_layout::AttribType::F32,
+            stride: self.ndim * std::mem::size_of::<f32>(),
+            offset: 0,
+            num_vtx: self.num_point,
+        })
+    }
+
+    /// the `i_ebo`-th element buffer, its index type and the number of the indices
+    pub fn element_buffer(
+        &self,
+        i_ebo: usize,
+    ) -> Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)> {
+        let ebo = self.ebos.get(i_ebo)?;
+        Some((ebo.ebo?, ebo.index_type, ebo.elem_size))
     }
 
-    pub fn draw(&self, gl: &glow::Context, mat_modelview: &[f32; 16], mat_projection: &[f32; 16]) {
-        let mp1 = mat_projection;
+    /// draw the visible sub-meshes with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        self.draw_transformed(gl, &del_geo_core::mat4_col_major::from_identity());
+    }
+
+    /// draw with `mat_model` applied before the local transformations of the sub-meshes
+    /// (e.g., an instance of the mesh at another place)
+    pub fn draw_transformed(&self, gl: &glow::Context, mat_model: &[f32; 16]) {
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
-            // gl.clear_color(1.0, 1.0, 1.0, 1.0);
-            // gl.clear(glow::COLOR_BUFFER_BIT);
-            gl.use_program(self.program);
+            gl.use_program(Some(program.program));
             gl.bind_vertex_array(self.vertex_array);
-            for ebo in &self.ebos {
-                gl.enable(glow::DEPTH_TEST);
-                gl.uniform_3_f32(
-                    self.loc_color.as_ref(),
-                    ebo.color[0],
-                    ebo.color[1],
-                    ebo.color[2],
-                );
-                gl.uniform_matrix_4_f32_slice(
-                    self.loc_mat_modelview.as_ref(),
-                    false,
-                    mat_modelview,
-                );
-                gl.uniform_matrix_4_f32_slice(self.loc_mat_projection.as_ref(), false, mp1);
+            gl.enable(glow::DEPTH_TEST);
+            program.set_mat4(gl, "matLocal", mat_model).unwrap();
+            program.set_bool(gl, "isZFlip", self.is_z_flip).unwrap();
+            let clip_plane = self.clip_plane.unwrap_or([0.; 4]);
+            program.set_vec4(gl, "clipPlane", &clip_plane).unwrap();
+            let mut is_mat_local = false;
+            for ebo in self.ebos.iter().filter(|ebo| ebo.is_visible) {
+                program.set_vec3(gl, "color", &ebo.color).unwrap();
+                // uploaded again only around the sub-meshes with the transformation
+                if let Some(mat_local) = &ebo.mat_local {
+                    let m = del_geo_core::mat4_col_major::mult_mat_col_major(mat_model, mat_local);
+                    program.set_mat4(gl, "matLocal", &m).unwrap();
+                    is_mat_local = true;
+                } else if is_mat_local {
+                    program.set_mat4(gl, "matLocal", mat_model).unwrap();
+                    is_mat_local = false;
+                }
                 gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, ebo.ebo);
-                gl.draw_elements(ebo.mode, ebo.elem_size as i32, glow::UNSIGNED_INT, 0);
+                gl.draw_elements(ebo.mode, ebo.elem_size as i32, ebo.index_type.gl_type(), 0);
             }
             gl.bind_vertex_array(None);
         }
     }
 
-    pub fn draw_points(&self, gl: &glow::Context, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+    /// draw the vertices as the points with z flipped
+    pub fn draw_points(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
-            gl.use_program(self.program);
+            gl.use_program(Some(program.program));
             gl.bind_vertex_array(self.vertex_array);
-            gl.uniform_3_f32(self.loc_color.as_ref(), 0., 0., 0.);
-            gl.uniform_matrix_4_f32_slice(self.loc_mat_modelview.as_ref(), false, mat_modelview);
-            gl.uniform_matrix_4_f32_slice(self.loc_mat_projection.as_ref(), false, &mp1);
+            program.set_vec3(gl, "color", &[0., 0., 0.]).unwrap();
+            let identity = del_geo_core::mat4_col_major::from_identity();
+            program.set_mat4(gl, "matLocal", &identity).unwrap();
+            program.set_bool(gl, "isZFlip", true).unwrap();
+            let clip_plane = self.clip_plane.unwrap_or([0.; 4]);
+            program.set_vec4(gl, "clipPlane", &clip_plane).unwrap();
             gl.draw_arrays(glow::POINTS, 0, self.num_point as i32);
             gl.bind_vertex_array(None);
         }
@@ -180,8 +361,11 @@ void main() {
     pub fn destroy(&self, gl: &glow::Context) {
         use glow::HasContext as _;
         unsafe {
-            gl.delete_program(self.program.unwrap());
+            self.program.as_ref().unwrap().destroy(gl);
             gl.delete_vertex_array(self.vertex_array.unwrap());
+            if let Some(vbo) = self.vbo_xyz {
+                gl.delete_buffer(vbo);
+            }
         }
     }
 
@@ -1,27 +1,157 @@
-//! draw mesh position. The RGB color is defined par index
+//! draw mesh position with the texture coordinates.
+//! Each element buffer has a [`Material`]: the texture modulated by the base color,
+//! the transformation of the UVs and an optional second texture
 
 use glow::HasContext;
 
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform mat3 uvTransform;
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec2 texIn;
+out vec2 texPrj;
+out vec3 posView;
+
+void main() {
+    vec4 p = frameMatView * vec4(position, 1.0);
+    gl_Position = frame_project(p, true);
+    posView = p.xyz;
+    texPrj = (uvTransform * vec3(texIn, 1.0)).xy;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "normal_output"
+uniform sampler2D myTextureSampler;
+uniform sampler2D secondTexture;
+uniform vec4 baseColor;
+uniform bool is_texture;
+uniform int secondTextureUsage; // 0: none, 1: detail, 2: normal map
+
+in vec2 texPrj;
+in vec3 posView;
+layout (location = 0) out vec4 FragColor;
+
+// normal map in the tangent frame computed from the derivatives of the position and the UV
+vec3 perturb_normal(vec3 n_map) {
+    vec3 dp1 = dFdx(posView);
+    vec3 dp2 = dFdy(posView);
+    vec2 duv1 = dFdx(texPrj);
+    vec2 duv2 = dFdy(texPrj);
+    vec3 n = normalize(cross(dp1, dp2));
+    vec3 dp2perp = cross(dp2, n);
+    vec3 dp1perp = cross(n, dp1);
+    vec3 t = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 b = dp2perp * duv1.y + dp1perp * duv2.y;
+    float invmax = inversesqrt(max(max(dot(t, t), dot(b, b)), 1.0e-20));
+    return normalize(mat3(t * invmax, b * invmax, n) * n_map);
+}
+
+void main() {
+    vec4 c = baseColor;
+    if( is_texture ){
+        c *= texture(myTextureSampler, texPrj);
+    }
+    if( secondTextureUsage == 1 ){
+        c.rgb *= texture(secondTexture, texPrj).rgb;
+    }
+    else if( secondTextureUsage == 2 ){
+        vec3 n = perturb_normal(texture(secondTexture, texPrj).xyz * 2.0 - 1.0);
+        c.rgb *= 0.3 + 0.7 * abs(n.z);
+    }
+    FragColor = c;
+    write_normal(flat_normal(posView));
+}
+"#;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecondTextureUsage {
+    /// the color is multiplied by the RGB of the second texture
+    Detail,
+    /// tangent-space normal map shaded with the light from the eye
+    NormalMap,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    /// RGBA multiplied to the texture. The alpha smaller than 1 is blended
+    pub base_color: [f32; 4],
+    /// sample the texture. It is bound to the unit 0 by the caller if `texture` is `None`
+    pub is_texture: bool,
+    pub texture: Option<glow::NativeTexture>,
+    /// the UVs are scaled, rotated (radian) and then translated
+    pub uv_offset: [f32; 2],
+    pub uv_scale: [f32; 2],
+    pub uv_rotation: f32,
+    pub second_texture: Option<(glow::NativeTexture, SecondTextureUsage)>,
+}
+
+impl Material {
+    /// flat color without the texture
+    pub fn from_color(rgb: [f32; 3]) -> Self {
+        Material {
+            base_color: [rgb[0], rgb[1], rgb[2], 1.0],
+            is_texture: false,
+            ..Default::default()
+        }
+    }
+
+    /// texture with the white base color
+    pub fn from_texture(texture: Option<glow::NativeTexture>) -> Self {
+        Material {
+            is_texture: true,
+            texture,
+            ..Default::default()
+        }
+    }
+
+    /// column-major 3x3 matrix of the UV transformation
+    pub fn uv_transform(&self) -> [f32; 9] {
+        let (s, c) = self.uv_rotation.sin_cos();
+        let [sx, sy] = self.uv_scale;
+        let [ox, oy] = self.uv_offset;
+        [c * sx, s * sx, 0., -s * sy, c * sy, 0., ox, oy, 1.]
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            base_color: [1.0; 4],
+            is_texture: true,
+            texture: None,
+            uv_offset: [0.0; 2],
+            uv_scale: [1.0; 2],
+            uv_rotation: 0.0,
+            second_texture: None,
+        }
+    }
+}
+
 struct ElementBufferObject {
     mode: u32,
     elem_size: usize,
+    index_type: crate::index_buffer::IndexType,
+    max_index: Option<u32>,
     ebo: Option<glow::NativeBuffer>,
-    color: Option<[f32; 3]>,
+    material: Material,
 }
 
 pub struct Drawer {
-    program: Option<glow::NativeProgram>,
+    program: Option<crate::shader_program::ShaderProgram>,
     pub ndim: usize,
     num_point: usize,
     vertex_array: Option<glow::NativeVertexArray>,
-    // uniform variables
-    loc_texture: Option<glow::NativeUniformLocation>,
-    loc_color: Option<glow::NativeUniformLocation>,
-    loc_is_texture: Option<glow::NativeUniformLocation>,
-    loc_mat_modelview: Option<glow::NativeUniformLocation>,
-    loc_mat_projection: Option<glow::NativeUniformLocation>,
+    vbo_xyz: Option<glow::NativeBuffer>,
+    /// reused by [`Drawer::set_vtx2uv`]
+    vbo_uv: Option<glow::NativeBuffer>,
     // elemenb buffer object
     ebos: Vec<ElementBufferObject>,
+    /// draw the mesh laid out in the UV space instead of the 3D mesh
+    pub is_uv_layout: bool,
+    /// set by [`crate::drawer_uv_layout::Drawer::set_tri2vtx_vtx2xyz_vtx2uv`]
+    pub drawer_uv_layout: crate::drawer_uv_layout::Drawer,
 }
 
 impl Drawer {
@@ -31,103 +161,131 @@ impl Drawer {
             ndim: 0,
             num_point: 0,
             vertex_array: None,
-            loc_texture: None,    // -1 is the failure flag
-            loc_color: None,      // -1 is the failure flag
-            loc_is_texture: None, // -1 is the failure flag
-            loc_mat_modelview: None,
-            loc_mat_projection: None,
+            vbo_xyz: None,
+            vbo_uv: None,
             ebos: Vec::<ElementBufferObject>::new(),
+            is_uv_layout: false,
+            drawer_uv_layout: crate::drawer_uv_layout::Drawer::new(),
         }
     }
 
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-
-        const VS_SRC: &str = r#"
-uniform mat4 matMV;
-uniform mat4 matPrj;
-
-layout (location = 0) in vec3 position;
-layout (location = 1) in vec2 texIn;
-out vec2 texPrj;
-
-void main() {
-    gl_Position = matPrj * matMV * vec4(position, 1.0);
-    texPrj = texIn;
-    // gl_Position = vec4(position, 1.0);
-}
-"#;
-
-        const FS_SRC: &str = r#"
-uniform sampler2D myTextureSampler;
-uniform vec3 color;
-uniform bool is_texture;
-
-in vec2 texPrj;
-out vec4 FragColor;
-
-void main() {
-    if( is_texture ){
-        FragColor = texture(myTextureSampler,texPrj);
-    }
-    else {
-        FragColor = vec4(color, 1.0);
-    }
-}
-"#;
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        self.set_program(gl, program);
         unsafe {
-            self.program = crate::compile_shaders(gl, shader_version, VS_SRC, FS_SRC);
-            let program = self.program.unwrap();
-            self.loc_mat_modelview = gl.get_uniform_location(program, "matMV");
-            self.loc_mat_projection = gl.get_uniform_location(program, "matPrj");
-            self.loc_texture = gl.get_uniform_location(program, "myTextureSampler");
-            self.loc_color = gl.get_uniform_location(program, "color");
-            self.loc_is_texture = gl.get_uniform_location(program, "is_texture");
             let vao0 = gl.create_vertex_array().unwrap();
             self.vertex_array = Some(vao0);
         }
+        self.drawer_uv_layout.compile_shader(gl, builder);
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]).
+    /// The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
     }
 
+    /// add the element buffer stored with the smallest index type.
+    /// Drawn with the flat `color`, or with the texture bound to the unit 0 if it is `None`
     pub fn add_elem2vtx<T>(
         &mut self,
         gl: &glow::Context,
         mode: u32,
         elem2vtx: &[T],
         color: Option<[f32; 3]>,
-    ) where
-        T: 'static + Copy + num_traits::AsPrimitive<u32>,
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
     {
+        self.add_elem2vtx_with_index_type(gl, mode, elem2vtx, color, None)
+    }
+
+    /// add the element buffer stored with `index_type` (the smallest type if it is `None`).
+    /// Error if an index does not fit in `index_type` or is out of the range of the vertices
+    pub fn add_elem2vtx_with_index_type<T>(
+        &mut self,
+        gl: &glow::Context,
+        mode: u32,
+        elem2vtx: &[T],
+        color: Option<[f32; 3]>,
+        index_type: Option<crate::index_buffer::IndexType>,
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
+    {
+        let index_data = crate::index_buffer::IndexData::new(elem2vtx, index_type)?;
+        if self.num_point != 0 {
+            crate::validate::max_index("elem2vtx", index_data.max_index, self.num_point)?;
+        }
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
             let ebo0 = gl.create_buffer().unwrap();
             gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo0));
-            let elem_vtx0: Vec<u32> = elem2vtx.iter().map(|i| (*i).as_()).collect();
-            gl.buffer_data_u8_slice(
-                glow::ELEMENT_ARRAY_BUFFER,
-                bytemuck::cast_slice(&elem_vtx0),
-                glow::STATIC_DRAW,
-            );
+            index_data.upload(gl, glow::STATIC_DRAW);
             self.ebos.push(ElementBufferObject {
                 mode,
-                elem_size: elem_vtx0.len(),
+                elem_size: index_data.num_index,
+                index_type: index_data.index_type,
+                max_index: index_data.max_index,
                 ebo: Some(ebo0),
-                color,
+                material: match color {
+                    Some(rgb) => Material::from_color(rgb),
+                    None => Material::from_texture(None),
+                },
             });
             gl.bind_vertex_array(None);
         }
+        Ok(())
+    }
+
+    pub fn num_elem2vtx(&self) -> usize {
+        self.ebos.len()
     }
 
-    pub fn update_vtx2xyz(&mut self, gl: &glow::Context, vtx2xyz: &[f32], ndim: usize) {
+    pub fn material(&self, i_ebo: usize) -> &Material {
+        &self.ebos[i_ebo].material
+    }
+
+    pub fn material_mut(&mut self, i_ebo: usize) -> &mut Material {
+        &mut self.ebos[i_ebo].material
+    }
+
+    pub fn set_material(&mut self, i_ebo: usize, material: Material) {
+        self.ebos[i_ebo].material = material;
+    }
+
+    /// error if the length is not a multiple of `ndim`, the element buffers refer beyond it,
+    /// or (with [`crate::validate::IS_ENABLED`]) a coordinate is not finite
+    pub fn update_vtx2xyz(
+        &mut self,
+        gl: &glow::Context,
+        vtx2xyz: &[f32],
+        ndim: usize,
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), ndim)?;
+        crate::validate::finite("vtx2xyz", vtx2xyz)?;
+        let num_point = vtx2xyz.len() / ndim;
+        for ebo in &self.ebos {
+            crate::validate::max_index("elem2vtx", ebo.max_index, num_point)?;
+        }
         self.ndim = ndim;
-        self.num_point = vtx2xyz.len() / self.ndim;
+        self.num_point = num_point;
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
             //
-            let vbo = gl.create_buffer().unwrap();
+            let vbo = *self
+                .vbo_xyz
+                .get_or_insert_with(|| gl.create_buffer().unwrap());
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
@@ -135,9 +293,12 @@ void main() {
                 glow::STATIC_DRAW,
             );
 
-            let pos_attrib = gl
-                .get_attrib_location(self.program.unwrap(), "position")
-                .unwrap();
+            let Some(pos_attrib) = self.program.as_ref().unwrap().attrib_location("position")
+            else {
+                // optimized out by the driver as it does not contribute to the output
+                gl.bind_vertex_array(None);
+                return Ok(());
+            };
             gl.enable_vertex_attrib_array(pos_attrib);
             gl.vertex_attrib_pointer_f32(
                 pos_attrib,
@@ -149,24 +310,37 @@ void main() {
             );
             gl.bind_vertex_array(None);
         }
+        Ok(())
     }
 
-    pub fn set_vtx2uv(&mut self, gl: &glow::Context, vtx2tex: &[f32]) {
+    /// error if the number of the UVs differs from the number of the vertices
+    pub fn set_vtx2uv(&mut self, gl: &glow::Context, vtx2tex: &[f32]) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2uv", vtx2tex.len(), 2)?;
+        if self.num_point != 0 {
+            crate::validate::length("vtx2uv", vtx2tex.len(), self.num_point * 2)?;
+        }
+        let Some(program) = &self.program else {
+            return Err("vtx2uv: the shader is not compiled yet".to_string());
+        };
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
-            let vbo = gl.create_buffer().unwrap();
+            let vbo = *self
+                .vbo_uv
+                .get_or_insert_with(|| gl.create_buffer().unwrap());
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
                 bytemuck::cast_slice(vtx2tex),
                 glow::STATIC_DRAW,
             );
-            let pos_attrib = gl
-                .get_attrib_location(self.program.unwrap(), "texIn")
-                .unwrap();
-            gl.enable_vertex_attrib_array(pos_attrib);
+            let Some(uv_attrib) = program.attrib_location("texIn") else {
+                // optimized out by the driver as it does not contribute to the output
+                gl.bind_vertex_array(None);
+                return Ok(());
+            };
+            gl.enable_vertex_attrib_array(uv_attrib);
             gl.vertex_attrib_pointer_f32(
-                1,
+                uv_attrib,
                 2,
                 glow::FLOAT,
                 false,
@@ -175,53 +349,123 @@ void main() {
             ); // gl24
             gl.bind_vertex_array(None);
         }
+        Ok(())
+    }
+
+    /// the uploaded vertex positions (e.g., for [`crate::drawer_debug_overlay`]).
+    /// `None` before the positions are set
+    pub fn attrib_view_xyz(&self) -> Option<crate::vertex_layout::AttribView> {
+        Some(crate::vertex_layout::AttribView {
+            vbo: self.vbo_xyz?,
+            num_comp: self.ndim,
+            atype: crate::vertex_layout::AttribType::F32,
+            stride: self.ndim * std::mem::size_of::<f32>(),
+            offset: 0,
+            num_vtx: self.num_point,
+        })
     }
 
-    pub fn draw(&self, gl: &glow::Context, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+    /// the `i_ebo`-th element buffer, its index type and the number of the indices
+    pub fn element_buffer(
+        &self,
+        i_ebo: usize,
+    ) -> Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)> {
+        let ebo = self.ebos.get(i_ebo)?;
+        Some((ebo.ebo?, ebo.index_type, ebo.elem_size))
+    }
+
+    /// draw with the camera of [`crate::frame_uniforms`] with z flipped
+    pub fn draw(&self, gl: &glow::Context) {
+        if self.is_uv_layout {
+            self.drawer_uv_layout.draw(gl);
+            return;
+        }
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
-            gl.use_program(self.program);
+            gl.use_program(Some(program.program));
+            program.set_i32(gl, "myTextureSampler", 0).unwrap();
+            program.set_i32(gl, "secondTexture", 1).unwrap();
+            // state of the caller restored at the end
+            let active_texture = gl.get_parameter_i32(glow::ACTIVE_TEXTURE) as u32;
+            let mut prev_textures = [None; 2];
+            for (i, tex) in prev_textures.iter_mut().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + i as u32);
+                *tex = gl.get_parameter_texture(glow::TEXTURE_BINDING_2D);
+            }
+            gl.active_texture(glow::TEXTURE0);
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let blend_func = [
+                glow::BLEND_SRC_RGB,
+                glow::BLEND_DST_RGB,
+                glow::BLEND_SRC_ALPHA,
+                glow::BLEND_DST_ALPHA,
+            ]
+            .map(|param| gl.get_parameter_i32(param) as u32);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
             for ebo in &self.ebos {
-                match ebo.color {
-                    Some(color) => {
-                        gl.uniform_1_i32(self.loc_is_texture.as_ref(), 0);
-                        gl.uniform_3_f32(self.loc_color.as_ref(), color[0], color[1], color[2]);
-                    }
-                    _ => {
-                        gl.uniform_1_i32(self.loc_is_texture.as_ref(), 1);
-                    }
+                let m = &ebo.material;
+                // the opaque materials are drawn without the blending
+                if m.base_color[3] < 1.0 {
+                    gl.enable(glow::BLEND);
+                } else {
+                    gl.disable(glow::BLEND);
+                }
+                program.set_vec4(gl, "baseColor", &m.base_color).unwrap();
+                program.set_bool(gl, "is_texture", m.is_texture).unwrap();
+                program
+                    .set_mat3(gl, "uvTransform", &m.uv_transform())
+                    .unwrap();
+                if let Some(texture) = m.texture {
+                    gl.active_texture(glow::TEXTURE0);
+                    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
                 }
-                gl.uniform_matrix_4_f32_slice(
-                    self.loc_mat_modelview.as_ref(),
-                    false,
-                    mat_modelview,
-                );
-                gl.uniform_matrix_4_f32_slice(self.loc_mat_projection.as_ref(), false, &mp1);
+                let usage = match m.second_texture {
+                    Some((texture, usage)) => {
+                        gl.active_texture(glow::TEXTURE1);
+                        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                        gl.active_texture(glow::TEXTURE0);
+                        match usage {
+                            SecondTextureUsage::Detail => 1,
+                            SecondTextureUsage::NormalMap => 2,
+                        }
+                    }
+                    None => 0,
+                };
+                program.set_i32(gl, "secondTextureUsage", usage).unwrap();
                 gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, ebo.ebo);
-                gl.draw_elements(ebo.mode, ebo.elem_size as i32, glow::UNSIGNED_INT, 0);
+                gl.draw_elements(ebo.mode, ebo.elem_size as i32, ebo.index_type.gl_type(), 0);
+            }
+            for (i, tex) in prev_textures.into_iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + i as u32);
+                gl.bind_texture(glow::TEXTURE_2D, tex);
             }
+            gl.active_texture(active_texture);
+            if is_blend {
+                gl.enable(glow::BLEND);
+            } else {
+                gl.disable(glow::BLEND);
+            }
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func;
+            gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
             gl.bind_vertex_array(None);
         }
     }
 
-    pub fn draw_points(&self, gl: &glow::Context, mat_modelview: &[f32], mat_projection: &[f32]) {
-        let mp0 = mat_projection;
-        let mp1: [f32; 16] = [
-            // mp1 = [z flip] * mp0
-            mp0[0], mp0[1], -mp0[2], mp0[3], mp0[4], mp0[5], -mp0[6], mp0[7], mp0[8], mp0[9],
-            -mp0[10], mp0[11], mp0[12], mp0[13], -mp0[14], mp0[15],
-        ];
+    pub fn draw_points(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
-            gl.use_program(self.program);
-            gl.uniform_matrix_4_f32_slice(self.loc_mat_modelview.as_ref(), false, mat_modelview);
-            gl.uniform_matrix_4_f32_slice(self.loc_mat_projection.as_ref(), false, &mp1);
+            gl.use_program(Some(program.program));
+            program
+                .set_vec4(gl, "baseColor", &[0., 0., 0., 1.])
+                .unwrap();
+            program.set_bool(gl, "is_texture", false).unwrap();
+            program.set_i32(gl, "secondTextureUsage", 0).unwrap();
             gl.draw_arrays(glow::POINTS, 0, (self.num_point) as i32);
             gl.bind_vertex_array(None);
         }
@@ -230,9 +474,13 @@ void main() {
     pub fn destroy(&self, gl: &glow::Context) {
         use glow::HasContext as _;
         unsafe {
-            gl.delete_program(self.program.unwrap());
+            self.program.as_ref().unwrap().destroy(gl);
             gl.delete_vertex_array(self.vertex_array.unwrap());
+            for vbo in [self.vbo_xyz, self.vbo_uv].into_iter().flatten() {
+                gl.delete_buffer(vbo);
+            }
         }
+        self.drawer_uv_layout.destroy(gl);
     }
 }
 impl Default for Drawer {
@@ -240,3 +488,75 @@ impl Default for Drawer {
         Self::new()
     }
 }
+
+/// checkerboard of `num_cell` x `num_cell` cells in the UV square to inspect the
+/// parameterization. The texture is repeated outside the square
+pub fn checker_texture(
+    gl: &glow::Context,
+    resolution: usize,
+    num_cell: usize,
+) -> glow::NativeTexture {
+    let data: Vec<u8> = (0..resolution * resolution)
+        .flat_map(|i| {
+            let (ix, iy) = (i % resolution, i / resolution);
+            let is_white =
+                (ix * num_cell / resolution + iy * num_cell / resolution).is_multiple_of(2);
+            let v = if is_white { 230 } else { 50 };
+            [v, v, v, 255]
+        })
+        .collect();
+    create_texture_rgba8(gl, resolution, &data)
+}
+
+/// cells colored by their UV (red for u, green for v) with the grid lines, so that both the
+/// distortion and the orientation of the parameterization are visible.
+/// `num_cell` of zero is treated as one
+pub fn uv_grid_texture(
+    gl: &glow::Context,
+    resolution: usize,
+    num_cell: usize,
+) -> glow::NativeTexture {
+    let num_cell = num_cell.max(1);
+    let cell = (resolution / num_cell).max(1);
+    let data: Vec<u8> = (0..resolution * resolution)
+        .flat_map(|i| {
+            let (ix, iy) = (i % resolution, i / resolution);
+            if ix.is_multiple_of(cell) || iy.is_multiple_of(cell) {
+                return [0, 0, 0, 255];
+            }
+            let u = (ix / cell) as f32 / num_cell as f32;
+            let v = (iy / cell) as f32 / num_cell as f32;
+            [(64. + 191. * u) as u8, (64. + 191. * v) as u8, 160, 255]
+        })
+        .collect();
+    create_texture_rgba8(gl, resolution, &data)
+}
+
+fn create_texture_rgba8(gl: &glow::Context, resolution: usize, data: &[u8]) -> glow::NativeTexture {
+    unsafe {
+        let tex = gl.create_texture().unwrap();
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            resolution as i32,
+            resolution as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelUnpackData::Slice(Some(data)),
+        );
+        gl.generate_mipmap(glow::TEXTURE_2D);
+        for (param, value) in [
+            (glow::TEXTURE_MIN_FILTER, glow::LINEAR_MIPMAP_LINEAR),
+            (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+            (glow::TEXTURE_WRAP_S, glow::REPEAT),
+            (glow::TEXTURE_WRAP_T, glow::REPEAT),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, param, value as i32);
+        }
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        tex
+    }
+}
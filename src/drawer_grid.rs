@@ -0,0 +1,186 @@
+//! draw an anti-aliased infinite grid on an axis-aligned plane
+//!
+//! A triangle covering the viewport is drawn and each fragment intersects its view ray with the
+//! plane, so the grid extends to the horizon. The depth of the intersection is written such
+//! that the grid is occluded by the meshes drawn with the same camera of [`crate::frame_uniforms`].
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "vertex_id"
+out vec4 nearPoint;
+out vec4 farPoint;
+
+void main() {
+    vec2 p = fullscreen_triangle_uv() * 2.0 - 1.0;
+    mat4 mvp_inv = inverse(frameMatPrj * frameMatView);
+    nearPoint = mvp_inv * vec4(p, -1.0, 1.0);
+    farPoint = mvp_inv * vec4(p, 1.0, 1.0);
+    gl_Position = vec4(p, 0.0, 1.0);
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "antialias"
+#include "depth"
+uniform int normalAxis;
+uniform float offset;
+uniform float spacing;
+uniform float majorEvery;
+uniform vec4 colorMinor;
+uniform vec4 colorMajor;
+uniform float fadeDistance;
+
+in vec4 nearPoint;
+in vec4 farPoint;
+out vec4 FragColor;
+
+// coverage of the lines of the grid with the given spacing
+float grid(vec2 uv, float s) {
+    return max(isoline_coverage(uv.x, s, 1.0), isoline_coverage(uv.y, s, 1.0));
+}
+
+void main() {
+    vec3 p0 = nearPoint.xyz / nearPoint.w;
+    vec3 p1 = farPoint.xyz / farPoint.w;
+    float t = (offset - p0[normalAxis]) / (p1[normalAxis] - p0[normalAxis]);
+    if( !(t >= 0.0 && t <= 1.0) ){ discard; } // also for the singular camera
+    vec3 h = p0 + t * (p1 - p0);
+    vec2 uv = vec2(h[(normalAxis + 1) % 3], h[(normalAxis + 2) % 3]);
+    float minor = grid(uv, spacing);
+    float major = grid(uv, spacing * majorEvery);
+    vec4 color = mix(colorMinor * minor, colorMajor, major);
+    // highlight the coordinate axes lying on the plane
+    vec2 d = fwidth(uv);
+    vec3 axis_color[3] = vec3[3](vec3(1.0, 0.2, 0.2), vec3(0.2, 1.0, 0.2), vec3(0.2, 0.2, 1.0));
+    if( abs(uv.y) < d.y ){ color = vec4(axis_color[(normalAxis + 1) % 3], 1.0); }
+    if( abs(uv.x) < d.x ){ color = vec4(axis_color[(normalAxis + 2) % 3], 1.0); }
+    color.a *= 1.0 - smoothstep(0.0, fadeDistance, distance(h, p0));
+    if( color.a <= 0.0 ){ discard; }
+    FragColor = color;
+    gl_FragDepth = window_depth(frameMatPrj * frameMatView, h);
+}
+"#;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    /// index of the coordinate axis normal to the plane (0: YZ-plane, 1: ZX-plane, 2: XY-plane)
+    pub normal_axis: usize,
+    /// position of the plane along `normal_axis`
+    pub offset: f32,
+    /// spacing of the minor lines. The major lines are drawn every `major_every` minor lines
+    pub spacing: f32,
+    pub major_every: u32,
+    pub color_minor: [f32; 4],
+    pub color_major: [f32; 4],
+    /// the grid becomes transparent at this distance from the camera
+    pub fade_distance: f32,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            vertex_array: None,
+            normal_axis: 1,
+            offset: 0.0,
+            spacing: 0.1,
+            major_every: 10,
+            color_minor: [0.5, 0.5, 0.5, 0.4],
+            color_major: [0.3, 0.3, 0.3, 0.8],
+            fade_distance: 10.0,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        unsafe {
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            let vao0 = gl.create_vertex_array().unwrap();
+            self.vertex_array = Some(vao0);
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// draw the grid with blending. Draw this after the opaque meshes
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            // state of the caller restored at the end
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let blend_func = [
+                glow::BLEND_SRC_RGB,
+                glow::BLEND_DST_RGB,
+                glow::BLEND_SRC_ALPHA,
+                glow::BLEND_DST_ALPHA,
+            ]
+            .map(|param| gl.get_parameter_i32(param) as u32);
+            gl.enable(glow::DEPTH_TEST);
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            program
+                .set_i32(gl, "normalAxis", self.normal_axis as i32)
+                .unwrap();
+            program.set_f32(gl, "offset", &[self.offset]).unwrap();
+            program.set_f32(gl, "spacing", &[self.spacing]).unwrap();
+            program
+                .set_f32(gl, "majorEvery", &[self.major_every as f32])
+                .unwrap();
+            program
+                .set_vec4(gl, "colorMinor", &self.color_minor)
+                .unwrap();
+            program
+                .set_vec4(gl, "colorMajor", &self.color_major)
+                .unwrap();
+            program
+                .set_f32(gl, "fadeDistance", &[self.fade_distance])
+                .unwrap();
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            if !is_blend {
+                gl.disable(glow::BLEND);
+            }
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func;
+            gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            self.program.as_ref().unwrap().destroy(gl);
+            gl.delete_vertex_array(self.vertex_array.unwrap());
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
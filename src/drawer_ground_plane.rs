@@ -0,0 +1,206 @@
+//! draw a rectangular ground plane lit by a directional light that receives the shadow
+//!
+//! The light is the one of [`crate::frame_uniforms`]. Set
+//! [`crate::frame_uniforms::FrameUniforms::light_dir`] opposite to
+//! [`crate::shadow_map::ShadowMap::light_dir`] (the former is toward the light) for the shadow
+//! to match the shading
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform mat4 matShadow;
+
+layout (location = 0) in vec3 position;
+out vec4 shadowCoord;
+out vec3 posView;
+
+void main() {
+    vec4 p = frameMatView * vec4(position, 1.0);
+    gl_Position = frameMatPrj * p;
+    posView = p.xyz;
+    shadowCoord = matShadow * vec4(position, 1.0);
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "lighting"
+#include "normal_output"
+uniform vec3 color;
+uniform vec3 normal;
+uniform sampler2D shadowMap;
+uniform bool is_shadow;
+uniform float bias;
+uniform int pcfRadius;
+
+in vec4 shadowCoord;
+in vec3 posView;
+layout (location = 0) out vec4 FragColor;
+
+float lit_fraction() {
+    vec3 p = shadowCoord.xyz / shadowCoord.w;
+    if( p.x < 0.0 || p.x > 1.0 || p.y < 0.0 || p.y > 1.0 || p.z > 1.0 ){
+        return 1.0;
+    }
+    vec2 texel = 1.0 / vec2(textureSize(shadowMap, 0));
+    float lit = 0.0;
+    float count = 0.0;
+    for(int i = -pcfRadius; i <= pcfRadius; ++i){
+        for(int j = -pcfRadius; j <= pcfRadius; ++j){
+            float d = texture(shadowMap, p.xy + vec2(i, j) * texel).r;
+            lit += (p.z - bias > d) ? 0.0 : 1.0;
+            count += 1.0;
+        }
+    }
+    return lit / count;
+}
+
+void main() {
+    float diffuse = lambert(normal, normalize(frameLightDir.xyz)) * frameLightDir.w;
+    if( is_shadow ){
+        diffuse *= lit_fraction();
+    }
+    float ambient = frameLightColor.w;
+    float intensity = ambient + (1.0 - ambient) * diffuse;
+    FragColor = vec4(color * frameLightColor.rgb * intensity, 1.0);
+    write_normal(flat_normal(posView));
+}
+"#;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbo: Option<glow::NativeBuffer>,
+    pub color: [f32; 3],
+    normal: [f32; 3],
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            vertex_array: None,
+            vbo: None,
+            color: [0.8, 0.8, 0.8],
+            normal: [0., 1., 0.],
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        unsafe {
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            let vao0 = gl.create_vertex_array().unwrap();
+            self.vertex_array = Some(vao0);
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// set the rectangle `center + s * half_u + t * half_v` for `s, t` in [-1,1].
+    /// The front side is in the direction of `half_u x half_v`
+    pub fn set_rect(
+        &mut self,
+        gl: &glow::Context,
+        center: &[f32; 3],
+        half_u: &[f32; 3],
+        half_v: &[f32; 3],
+    ) {
+        use del_geo_core::vec3;
+        self.normal = vec3::normalize(&vec3::cross(half_u, half_v));
+        let mut vtx2xyz = Vec::<f32>::with_capacity(12);
+        for (s, t) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+            let p = vec3::add(
+                center,
+                &vec3::add(&vec3::scale(half_u, s), &vec3::scale(half_v, t)),
+            );
+            vtx2xyz.extend_from_slice(&p);
+        }
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            if let Some(vbo) = self.vbo.take() {
+                gl.delete_buffer(vbo);
+            }
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&vtx2xyz),
+                glow::STATIC_DRAW,
+            );
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(
+                0,
+                3,
+                glow::FLOAT,
+                false,
+                (3 * std::mem::size_of::<f32>()) as i32,
+                0,
+            );
+            gl.bind_vertex_array(None);
+            self.vbo = Some(vbo);
+        }
+    }
+
+    /// draw the plane. The shadow is computed if `shadow` is given
+    pub fn draw(&self, gl: &glow::Context, shadow: Option<&crate::shadow_map::ShadowMap>) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            gl.enable(glow::DEPTH_TEST);
+            program.set_vec3(gl, "color", &self.color).unwrap();
+            program.set_vec3(gl, "normal", &self.normal).unwrap();
+            match shadow {
+                Some(shadow) => {
+                    shadow.bind_texture(gl, 0);
+                    program.set_i32(gl, "shadowMap", 0).unwrap();
+                    program.set_bool(gl, "is_shadow", true).unwrap();
+                    program.set_f32(gl, "bias", &[shadow.bias]).unwrap();
+                    program.set_i32(gl, "pcfRadius", shadow.pcf_radius).unwrap();
+                    program
+                        .set_mat4(gl, "matShadow", &shadow.mat_world2shadow())
+                        .unwrap();
+                }
+                _ => {
+                    program.set_bool(gl, "is_shadow", false).unwrap();
+                }
+            }
+            gl.draw_arrays(glow::TRIANGLE_FAN, 0, 4);
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            self.program.as_ref().unwrap().destroy(gl);
+            gl.delete_vertex_array(self.vertex_array.unwrap());
+            if let Some(vbo) = self.vbo {
+                gl.delete_buffer(vbo);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,307 @@
+//! draw an image (e.g., a rendered result, a depth map or a 2D simulation grid) on a rectangle
+//!
+//! The image has 1 to 4 channels of `u8` or `f32`. Either the channels are shown as the color,
+//! or a selected channel is remapped from `value_range` and shown with a colormap.
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "vertex_id"
+uniform bool isNdc; // the box is in the normalized device coordinate
+uniform vec4 box; // min_x, min_y, max_x, max_y
+uniform bool isFlipY;
+out vec2 uv;
+
+void main() {
+    vec2 t = rectangle_strip_uv();
+    uv = isFlipY ? vec2(t.x, 1.0 - t.y) : t;
+    vec4 p = vec4(mix(box.xy, box.zw, t), 0.0, 1.0);
+    gl_Position = isNdc ? p : frameMatPrj * frameMatView * p;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "colormap"
+uniform sampler2D imageTex;
+uniform int numChannel;
+uniform int channel; // negative for the color display
+uniform int colormapIndex;
+uniform vec2 valueRange;
+in vec2 uv;
+out vec4 FragColor;
+
+void main() {
+    vec4 c = texture(imageTex, uv);
+    if( channel >= 0 ){
+        float v = channel == 0 ? c.r : channel == 1 ? c.g : channel == 2 ? c.b : c.a;
+        float t = (v - valueRange.x) / (valueRange.y - valueRange.x);
+        FragColor = vec4(colormap(colormapIndex, t), 1.0);
+        return;
+    }
+    c.rgb = (c.rgb - valueRange.x) / (valueRange.y - valueRange.x);
+    if( numChannel == 1 ){ c = vec4(c.rrr, 1.0); }
+    if( numChannel < 4 ){ c.a = 1.0; }
+    FragColor = c;
+}
+"#;
+
+pub struct Drawer {
+    /// the image occupies this rectangle `[min_x, min_y, max_x, max_y]`
+    pub aabb: [f32; 4],
+    /// `glow::NEAREST` or `glow::LINEAR`. The `f32` image is sampled with `glow::NEAREST` if
+    /// the target cannot filter it (see [`crate::render_target::is_f32_linear_filterable`])
+    pub filter: u32,
+    /// see [`Drawer::set_channel`]
+    channel: Option<usize>,
+    pub colormap: crate::colormap::Colormap,
+    /// values mapped to [0,1]. The `u8` values are in [0,1] in the shader
+    pub value_range: [f32; 2],
+    /// true if the first row is at the top (e.g., the images read from the files)
+    pub is_flip_y: bool,
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    tex_image: Option<glow::NativeTexture>,
+    num_channel: usize,
+    /// false for the `f32` image on the target without the linear filtering of it
+    is_linear_filterable: bool,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            aabb: [-1.0, -1.0, 1.0, 1.0],
+            filter: glow::NEAREST,
+            channel: None,
+            colormap: crate::colormap::Colormap::Grayscale,
+            value_range: [0.0, 1.0],
+            is_flip_y: false,
+            is_linear_filterable: true,
+            program: None,
+            vertex_array: None,
+            tex_image: None,
+            num_channel: 0,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        self.set_program(gl, program);
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+        }
+    }
+
+    /// `None` to show the channels as the color, otherwise the channel shown with `colormap`.
+    /// Error if the channel is not less than the number of the channels of the image
+    pub fn set_channel(&mut self, channel: Option<usize>) -> Result<(), String> {
+        if let Some(channel) = channel {
+            if channel >= self.num_channel {
+                return Err(format!(
+                    "image: the channel {channel} is out of the {} channels",
+                    self.num_channel
+                ));
+            }
+        }
+        self.channel = channel;
+        Ok(())
+    }
+
+    pub fn channel(&self) -> Option<usize> {
+        self.channel
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// `data[(j * width + i) * num_channel + k]` is the `k`-th channel of the pixel `(i, j)`
+    pub fn set_image_u8(
+        &mut self,
+        gl: &glow::Context,
+        (width, height): (usize, usize),
+        num_channel: usize,
+        data: &[u8],
+    ) -> Result<(), String> {
+        let internal_format = [glow::R8, glow::RG8, glow::RGB8, glow::RGBA8];
+        self.upload(
+            gl,
+            (width, height),
+            num_channel,
+            &internal_format,
+            glow::UNSIGNED_BYTE,
+            data,
+        )
+    }
+
+    /// `data[(j * width + i) * num_channel + k]` is the `k`-th channel of the pixel `(i, j)`.
+    /// The values are stored as the 32-bit floats without the loss of the precision
+    pub fn set_image_f32(
+        &mut self,
+        gl: &glow::Context,
+        (width, height): (usize, usize),
+        num_channel: usize,
+        data: &[f32],
+    ) -> Result<(), String> {
+        let internal_format = [glow::R32F, glow::RG32F, glow::RGB32F, glow::RGBA32F];
+        self.upload(
+            gl,
+            (width, height),
+            num_channel,
+            &internal_format,
+            glow::FLOAT,
+            bytemuck::cast_slice(data),
+        )
+    }
+
+    fn upload(
+        &mut self,
+        gl: &glow::Context,
+        (width, height): (usize, usize),
+        num_channel: usize,
+        internal_format: &[u32; 4],
+        ty: u32,
+        data: &[u8],
+    ) -> Result<(), String> {
+        if !(1..=4).contains(&num_channel) {
+            return Err(format!("image: {num_channel} channels is not supported"));
+        }
+        let size_of = if ty == glow::FLOAT { 4 } else { 1 };
+        crate::validate::length("image", data.len(), width * height * num_channel * size_of)?;
+        let format = [glow::RED, glow::RG, glow::RGB, glow::RGBA][num_channel - 1];
+        self.num_channel = num_channel;
+        self.is_linear_filterable =
+            ty != glow::FLOAT || crate::render_target::is_f32_linear_filterable(gl);
+        // the selected channel may not exist in the new image
+        self.channel = self.channel.filter(|&c| c < num_channel);
+        unsafe {
+            let tex = *self
+                .tex_image
+                .get_or_insert_with(|| gl.create_texture().unwrap());
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                internal_format[num_channel - 1] as i32,
+                width as i32,
+                height as i32,
+                0,
+                format,
+                ty,
+                glow::PixelUnpackData::Slice(Some(data)),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Ok(())
+    }
+
+    /// draw with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        self.draw_box(gl, false, &self.aabb);
+    }
+
+    /// draw the image covering the whole viewport regardless of `aabb`
+    pub fn draw_fullscreen(&self, gl: &glow::Context) {
+        self.draw_box(gl, true, &[-1.0, -1.0, 1.0, 1.0]);
+    }
+
+    fn draw_box(&self, gl: &glow::Context, is_ndc: bool, aabb: &[f32; 4]) {
+        let (Some(program), Some(tex_image)) = (&self.program, self.tex_image) else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex_image));
+            let filter = if self.is_linear_filterable {
+                self.filter
+            } else {
+                glow::NEAREST
+            };
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter as i32);
+            program.set_i32(gl, "imageTex", 0).unwrap();
+            program.set_bool(gl, "isNdc", is_ndc).unwrap();
+            program.set_vec4(gl, "box", aabb).unwrap();
+            program.set_bool(gl, "isFlipY", self.is_flip_y).unwrap();
+            program
+                .set_i32(gl, "numChannel", self.num_channel as i32)
+                .unwrap();
+            program
+                .set_i32(gl, "channel", self.channel.map_or(-1, |c| c as i32))
+                .unwrap();
+            program
+                .set_i32(gl, "colormapIndex", self.colormap.glsl_index())
+                .unwrap();
+            program
+                .set_vec2(gl, "valueRange", &self.value_range)
+                .unwrap();
+            // the blending of the caller is restored at the end
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let blend_func = [
+                glow::BLEND_SRC_RGB,
+                glow::BLEND_DST_RGB,
+                glow::BLEND_SRC_ALPHA,
+                glow::BLEND_DST_ALPHA,
+            ]
+            .map(|param| gl.get_parameter_i32(param) as u32);
+            if self.num_channel == 4 && self.channel.is_none() {
+                gl.enable(glow::BLEND);
+                gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            }
+            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func;
+            gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+            if !is_blend {
+                gl.disable(glow::BLEND);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(program) = self.program.take() {
+                program.destroy(gl);
+            }
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            if let Some(tex) = self.tex_image.take() {
+                gl.delete_texture(tex);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,397 @@
+//! interactive measurement of the distance, the angle and the area on a triangle mesh
+//!
+//! The clicked pixels are picked on the surface by casting the rays against the triangles.
+//! The measurement lines and the labels are drawn over the scene at the world positions,
+//! so they follow the camera.
+//!
+//! * [`Mode::Distance`]: two points
+//! * [`Mode::Angle`]: three points. The angle is at the second point
+//! * [`Mode::Area`]: a polygon of three or more points closed by [`Drawer::finish`]
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Distance,
+    Angle,
+    Area,
+}
+
+impl Mode {
+    /// number of the points completing the measurement (`None` for the polygon)
+    fn num_point(&self) -> Option<usize> {
+        match self {
+            Mode::Distance => Some(2),
+            Mode::Angle => Some(3),
+            Mode::Area => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub mode: Mode,
+    pub points: Vec<[f32; 3]>,
+}
+
+impl Measurement {
+    /// distance, angle in degrees, or area in world units. The angle is `NaN` if a point
+    /// coincides with the vertex of the angle
+    pub fn value(&self) -> f32 {
+        let p = &self.points;
+        match self.mode {
+            Mode::Distance => del_geo_core::edge3::length(&p[0], &p[1]),
+            Mode::Angle => {
+                let a = del_geo_core::vec3::sub(&p[0], &p[1]);
+                let b = del_geo_core::vec3::sub(&p[2], &p[1]);
+                let len = del_geo_core::vec3::norm(&a) * del_geo_core::vec3::norm(&b);
+                if len <= f32::EPSILON {
+                    return f32::NAN;
+                }
+                let c = del_geo_core::vec3::dot(&a, &b) / len;
+                c.clamp(-1.0, 1.0).acos().to_degrees()
+            }
+            Mode::Area => {
+                // magnitude of the vector area (exact for the planar polygons)
+                let mut a = [0f32; 3];
+                for i in 0..p.len() {
+                    let c = del_geo_core::vec3::cross(&p[i], &p[(i + 1) % p.len()]);
+                    a = del_geo_core::vec3::add(&a, &c);
+                }
+                0.5 * del_geo_core::vec3::norm(&a)
+            }
+        }
+    }
+
+    /// position of the label
+    fn anchor(&self) -> [f32; 3] {
+        match self.mode {
+            Mode::Angle => self.points[1],
+            Mode::Distance | Mode::Area => {
+                let mut c = [0f32; 3];
+                for p in &self.points {
+                    c = del_geo_core::vec3::add(&c, p);
+                }
+                del_geo_core::vec3::scale(&c, 1.0 / self.points.len() as f32)
+            }
+        }
+    }
+
+    fn label(&self, num_digit: usize) -> String {
+        let v = self.value();
+        if v.is_nan() {
+            return "-".to_string();
+        }
+        match self.mode {
+            Mode::Distance => format!("{v:.num_digit$}"),
+            Mode::Angle => format!("{v:.1}deg"),
+            Mode::Area => format!("{v:.num_digit$}"),
+        }
+    }
+
+    /// the line segments as the pairs of the points
+    fn segments(&self, is_closed: bool) -> Vec<[[f32; 3]; 2]> {
+        let p = &self.points;
+        let mut segments: Vec<_> = p.windows(2).map(|w| [w[0], w[1]]).collect();
+        if is_closed && p.len() > 2 {
+            segments.push([p[p.len() - 1], p[0]]);
+        }
+        segments
+    }
+}
+
+pub struct Drawer {
+    mode: Mode,
+    pub color: [f32; 3],
+    /// color of the measurement in progress
+    pub color_active: [f32; 3],
+    /// number of the digits after the decimal point of the distance and the area
+    pub num_digit: usize,
+    tri2vtx: Vec<usize>,
+    vtx2xyz: Vec<f32>,
+    /// points of the measurement in progress
+    points: Vec<[f32; 3]>,
+    measurements: Vec<Measurement>,
+    drawer_line: crate::drawer_vtx2xyrgb::Drawer,
+    drawer_point: crate::drawer_vtx2xyz::Drawer,
+    drawer_text: crate::drawer_text::Drawer,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        let mut drawer_line = crate::drawer_vtx2xyrgb::Drawer::new();
+        drawer_line.mode = glow::LINES;
+        let mut drawer_text = crate::drawer_text::Drawer::new();
+        drawer_text.is_depth_test = false;
+        Drawer {
+            mode: Mode::Distance,
+            color: [1.0, 0.5, 0.0],
+            color_active: [0.0, 0.6, 1.0],
+            num_digit: 3,
+            tri2vtx: vec![],
+            vtx2xyz: vec![],
+            points: vec![],
+            measurements: vec![],
+            drawer_line,
+            drawer_point: crate::drawer_vtx2xyz::Drawer::new(),
+            drawer_text,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_line.compile_shader(gl, builder);
+        self.drawer_point.compile_shader(gl, builder);
+        self.drawer_text.compile_shader(gl, builder);
+    }
+
+    /// the surface on which the points are picked
+    pub fn set_tri2vtx_vtx2xyz(
+        &mut self,
+        tri2vtx: &[usize],
+        vtx2xyz: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("tri2vtx", tri2vtx.len(), 3)?;
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::index_range("tri2vtx", tri2vtx, vtx2xyz.len() / 3)?;
+        self.tri2vtx = tri2vtx.to_vec();
+        self.vtx2xyz = vtx2xyz.to_vec();
+        Ok(())
+    }
+
+    /// the nearest point on the surface under the pixel. `pix_coord` is in pixels with the
+    /// origin at the top-left corner of the viewport of `image_size`
+    pub fn pick(
+        &self,
+        pix_coord: (f32, f32),
+        image_size: (f32, f32),
+        mat_modelview: &[f32; 16],
+        mat_projection: &[f32; 16],
+    ) -> Option<[f32; 3]> {
+        let mvp = del_geo_core::mat4_col_major::mult_mat_col_major(mat_projection, mat_modelview);
+        let mvp_inv = del_geo_core::mat4_col_major::try_inverse(&mvp)?;
+        // from the front (NDC z=+1) to the back, so the smallest parameter is the nearest
+        let (ray_org, ray_dir) =
+            del_geo_core::mat4_col_major::ray_from_transform_ndc2world_and_pixel_coordinates(
+                pix_coord,
+                &image_size,
+                &mvp_inv,
+            );
+        let xyz = |i_vtx: usize| arrayref::array_ref![self.vtx2xyz, i_vtx * 3, 3];
+        self.tri2vtx
+            .chunks(3)
+            .filter_map(|t| {
+                del_geo_core::tri3::intersection_against_line(
+                    xyz(t[0]),
+                    xyz(t[1]),
+                    xyz(t[2]),
+                    &ray_org,
+                    &ray_dir,
+                )
+                .map(|(t, _)| t)
+            })
+            .filter(|&t| t >= 0.0)
+            .min_by(|a, b| a.total_cmp(b))
+            .map(|t| del_geo_core::vec3::axpy(t, &ray_dir, &ray_org))
+    }
+
+    /// pick a point under the pixel and add it to the measurement in progress. The measurement
+    /// is completed when it has enough points. Returns the picked point
+    pub fn click(
+        &mut self,
+        gl: &glow::Context,
+        pix_coord: (f32, f32),
+        image_size: (f32, f32),
+        mat_modelview: &[f32; 16],
+        mat_projection: &[f32; 16],
+    ) -> Option<[f32; 3]> {
+        let p = self.pick(pix_coord, image_size, mat_modelview, mat_projection)?;
+        self.points.push(p);
+        if self
+            .mode
+            .num_point()
+            .is_some_and(|num_point| self.points.len() >= num_point)
+        {
+            self.finish(gl);
+        } else {
+            self.update(gl);
+        }
+        Some(p)
+    }
+
+    /// complete the measurement in progress (e.g., to close the polygon of [`Mode::Area`]).
+    /// It is discarded if it does not have enough points
+    pub fn finish(&mut self, gl: &glow::Context) {
+        let num_point = self.mode.num_point().unwrap_or(3);
+        let points = std::mem::take(&mut self.points);
+        if points.len() >= num_point {
+            self.measurements.push(Measurement {
+                mode: self.mode,
+                points,
+            });
+        }
+        self.update(gl);
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// change the mode. The measurement in progress is discarded as its points belong to the
+    /// previous mode
+    pub fn set_mode(&mut self, gl: &glow::Context, mode: Mode) {
+        if mode == self.mode {
+            return;
+        }
+        self.mode = mode;
+        self.cancel(gl);
+    }
+
+    /// discard the measurement in progress
+    pub fn cancel(&mut self, gl: &glow::Context) {
+        self.points.clear();
+        self.update(gl);
+    }
+
+    /// delete all the measurements
+    pub fn clear(&mut self, gl: &glow::Context) {
+        self.points.clear();
+        self.measurements.clear();
+        self.update(gl);
+    }
+
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    /// upload the lines, the points and the labels
+    fn update(&mut self, gl: &glow::Context) {
+        let active = Measurement {
+            mode: self.mode,
+            points: self.points.clone(),
+        };
+        let mut vtx2xyzrgb = Vec::<f32>::new();
+        let mut vtx2xyz = Vec::<f32>::new();
+        self.drawer_text.clear();
+        for (m, rgb, is_active) in self
+            .measurements
+            .iter()
+            .map(|m| (m, self.color, false))
+            .chain(std::iter::once((&active, self.color_active, true)))
+        {
+            for segment in m.segments(m.mode == Mode::Area && !is_active) {
+                for p in segment {
+                    vtx2xyzrgb.extend_from_slice(&p);
+                    vtx2xyzrgb.extend_from_slice(&rgb);
+                }
+            }
+            for p in &m.points {
+                vtx2xyz.extend_from_slice(p);
+            }
+            if !is_active {
+                self.drawer_text.add_label(
+                    crate::drawer_text::Anchor::World(m.anchor()),
+                    &m.label(self.num_digit),
+                    rgb,
+                );
+            }
+        }
+        self.drawer_line.set_vtx2xyzrgb(gl, &vtx2xyzrgb).unwrap();
+        self.drawer_point.set_vtx2xyz(gl, &vtx2xyz).unwrap();
+        self.drawer_text.update(gl);
+    }
+
+    /// draw over the scene without the depth test
+    pub fn draw(&self, gl: &glow::Context) {
+        use glow::HasContext;
+        unsafe {
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            gl.disable(glow::DEPTH_TEST);
+            self.drawer_line.draw(gl);
+            self.drawer_point.draw(gl);
+            self.drawer_text.draw(gl);
+            if is_depth_test {
+                gl.enable(glow::DEPTH_TEST);
+            }
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.drawer_line.destroy(gl);
+        self.drawer_point.destroy(gl);
+        self.drawer_text.destroy(gl);
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_value() {
+        use super::{Measurement, Mode};
+        let m = Measurement {
+            mode: Mode::Distance,
+            points: vec![[0.0, 0.0, 0.0], [3.0, 4.0, 0.0]],
+        };
+        assert!((m.value() - 5.0).abs() < 1.0e-5);
+        let m = Measurement {
+            mode: Mode::Angle,
+            points: vec![[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 2.0, 0.0]],
+        };
+        assert!((m.value() - 90.0).abs() < 1.0e-4);
+        // the second click on the vertex of the angle
+        let m = Measurement {
+            mode: Mode::Angle,
+            points: vec![[1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]],
+        };
+        assert!(m.value().is_nan());
+        assert_eq!(m.label(3), "-");
+        // the unit square on a plane not passing through the origin
+        let m = Measurement {
+            mode: Mode::Area,
+            points: vec![
+                [0.0, 0.0, 1.0],
+                [1.0, 0.0, 1.0],
+                [1.0, 1.0, 1.0],
+                [0.0, 1.0, 1.0],
+            ],
+        };
+        assert!((m.value() - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_pick_front_layer() {
+        // two overlapping triangles, the front one at z=0.5 and the back one at z=-0.5
+        #[rustfmt::skip]
+        let vtx2xyz = [
+            -1.0, -1.0, 0.5, 1.0, -1.0, 0.5, 0.0, 1.0, 0.5,
+            -1.0, -1.0, -0.5, 1.0, -1.0, -0.5, 0.0, 1.0, -0.5,
+        ];
+        let mut drawer = super::Drawer::new();
+        // the back triangle first so that the order of the triangles does not decide the hit
+        drawer
+            .set_tri2vtx_vtx2xyz(&[3, 4, 5, 0, 1, 2], &vtx2xyz)
+            .unwrap();
+        let identity = del_geo_core::mat4_col_major::from_identity::<f32>();
+        let p = drawer
+            .pick((50.0, 50.0), (100.0, 100.0), &identity, &identity)
+            .unwrap();
+        assert!(del_geo_core::edge3::length(&p, &[0.0, 0.0, 0.5]) < 1.0e-5);
+        // rotated by 180 degrees around the y-axis, the other triangle is in the front
+        let rot = del_geo_core::mat4_col_major::from_rot_y(std::f32::consts::PI);
+        let p = drawer
+            .pick((50.0, 50.0), (100.0, 100.0), &rot, &identity)
+            .unwrap();
+        assert!(del_geo_core::edge3::length(&p, &[0.0, 0.0, -0.5]) < 1.0e-5);
+        // outside the triangles
+        assert!(drawer
+            .pick((2.0, 2.0), (100.0, 100.0), &identity, &identity)
+            .is_none());
+    }
+}
@@ -0,0 +1,310 @@
+//! draw a mesh with user-supplied shaders. The buffers are set up by the [`VertexLayout`]
+//!
+//! The shaders read the camera by `#include "frame_uniforms"` (see [`crate::frame_uniforms`]).
+//! Other uniforms can be set through [`Drawer::program`] after `gl.use_program`.
+//!
+//! [`VertexLayout`]: crate::vertex_layout::VertexLayout
+
+use glow::HasContext;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    pub layout: crate::vertex_layout::VertexLayout,
+    pub mode: u32,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbos: Vec<Option<glow::NativeBuffer>>,
+    ebo: Option<glow::NativeBuffer>,
+    /// number of the vertices (or the instances) uploaded to each buffer
+    buffer2num_vtx: Vec<usize>,
+    /// number of the vertices in all the per-vertex buffers (drawn if there is no element buffer)
+    num_vtx: usize,
+    num_elem2vtx: usize,
+    index_type: crate::index_buffer::IndexType,
+    max_index: Option<u32>,
+    /// number of the instances. Zero means non-instanced drawing
+    pub num_instance: usize,
+}
+
+impl Drawer {
+    pub fn new(layout: crate::vertex_layout::VertexLayout, mode: u32) -> Self {
+        Drawer {
+            program: None,
+            layout,
+            mode,
+            vertex_array: None,
+            vbos: vec![],
+            buffer2num_vtx: vec![],
+            ebo: None,
+            num_vtx: 0,
+            num_elem2vtx: 0,
+            index_type: crate::index_buffer::IndexType::U32,
+            max_index: None,
+            num_instance: 0,
+        }
+    }
+
+    /// compile the sources without the version header (it is added for the current target).
+    /// The sources can `#include` the snippets of [`crate::shader_builder::SNIPPETS`]
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<(), String> {
+        let program = builder.build(gl, vertex_shader_source, fragment_shader_source, &[])?;
+        self.set_program(gl, program);
+        Ok(())
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]).
+    /// The previous program is released and the buffers are bound to the new attribute locations
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+        unsafe {
+            match self.vertex_array {
+                None => self.vertex_array = Some(gl.create_vertex_array().unwrap()),
+                Some(vao) => {
+                    // the locations enabled for the previous program may not be used anymore
+                    let num_attrib = gl.get_parameter_i32(glow::MAX_VERTEX_ATTRIBS) as u32;
+                    gl.bind_vertex_array(Some(vao));
+                    for location in 0..num_attrib {
+                        gl.disable_vertex_attrib_array(location);
+                    }
+                    gl.bind_vertex_array(None);
+                }
+            }
+        }
+        self.vbos.resize(self.layout.buffers.len(), None);
+        self.buffer2num_vtx.resize(self.layout.buffers.len(), 0);
+        // the attribute locations may change with the new program
+        for i_buffer in 0..self.vbos.len() {
+            if let Some(vbo) = self.vbos[i_buffer] {
+                self.bind_attribs(gl, i_buffer, vbo);
+            }
+        }
+    }
+
+    /// reflected program. Bind it with `gl.use_program` before setting the uniforms
+    pub fn program(&self) -> Option<&crate::shader_program::ShaderProgram> {
+        self.program.as_ref()
+    }
+
+    fn bind_attribs(&self, gl: &glow::Context, i_buffer: usize, vbo: glow::NativeBuffer) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            self.layout.apply(gl, program, i_buffer, vbo);
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    /// upload the data of the buffer `i_buffer` of the layout. Error if it is called before
+    /// `compile_shader`, if `i_buffer` is not in the layout, or if the data is not a multiple of the stride
+    pub fn set_buffer<T: bytemuck::Pod>(
+        &mut self,
+        gl: &glow::Context,
+        i_buffer: usize,
+        data: &[T],
+    ) -> Result<(), String> {
+        if self.program.is_none() {
+            return Err("set_buffer is called before compile_shader".to_string());
+        }
+        let Some(buffer) = self.layout.buffers.get(i_buffer) else {
+            return Err(format!(
+                "buffer {i_buffer} is out of the {} buffers of the layout",
+                self.layout.buffers.len()
+            ));
+        };
+        let stride = buffer.stride();
+        let num_byte = std::mem::size_of_val(data);
+        crate::validate::multiple_of("buffer (bytes)", num_byte, stride)?;
+        let num_vtx = num_byte / stride;
+        if buffer.divisor == 0 {
+            crate::validate::max_index("elem2vtx", self.max_index, num_vtx)?;
+        }
+        unsafe {
+            let vbo = match self.vbos[i_buffer] {
+                Some(vbo) => vbo,
+                None => {
+                    let vbo = gl.create_buffer().unwrap();
+                    self.vbos[i_buffer] = Some(vbo);
+                    self.bind_attribs(gl, i_buffer, vbo);
+                    vbo
+                }
+            };
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(data),
+                glow::DYNAMIC_DRAW,
+            );
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+        self.buffer2num_vtx[i_buffer] = num_vtx;
+        self.num_vtx = self.num_vtx_per_vertex_buffers().unwrap_or(0);
+        Ok(())
+    }
+
+    /// the smallest number of the vertices among the uploaded per-vertex buffers
+    fn num_vtx_per_vertex_buffers(&self) -> Option<usize> {
+        self.layout
+            .buffers
+            .iter()
+            .zip(self.vbos.iter().zip(self.buffer2num_vtx.iter()))
+            .filter(|(buffer, (vbo, _))| buffer.divisor == 0 && vbo.is_some())
+            .map(|(_, (_, &num_vtx))| num_vtx)
+            .min()
+    }
+
+    /// set the indices stored with `index_type` (the smallest type if it is `None`).
+    /// Error if it is called before `compile_shader`
+    pub fn set_elem2vtx<T>(
+        &mut self,
+        gl: &glow::Context,
+        elem2vtx: &[T],
+        index_type: Option<crate::index_buffer::IndexType>,
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
+    {
+        if self.vertex_array.is_none() {
+            return Err("set_elem2vtx is called before compile_shader".to_string());
+        }
+        let index_data = crate::index_buffer::IndexData::new(elem2vtx, index_type)?;
+        if let Some(num_vtx) = self.num_vtx_per_vertex_buffers() {
+            crate::validate::max_index("elem2vtx", index_data.max_index, num_vtx)?;
+        }
+        self.num_elem2vtx = index_data.num_index;
+        self.index_type = index_data.index_type;
+        self.max_index = index_data.max_index;
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            if self.ebo.is_none() {
+                self.ebo = Some(gl.create_buffer().unwrap());
+            }
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, self.ebo);
+            index_data.upload(gl, glow::STATIC_DRAW);
+            gl.bind_vertex_array(None);
+        }
+        Ok(())
+    }
+
+    /// the attribute `name` of the layout in the uploaded buffer
+    pub fn attrib_view(&self, name: &str) -> Option<crate::vertex_layout::AttribView> {
+        let location = crate::vertex_layout::AttribLocation::from(name);
+        self.layout
+            .buffers
+            .iter()
+            .enumerate()
+            .find_map(|(i_buffer, buffer)| {
+                let attrib = buffer.attribs.iter().find(|a| a.location == location)?;
+                let vbo = (*self.vbos.get(i_buffer)?)?;
+                Some(crate::vertex_layout::AttribView {
+                    vbo,
+                    num_comp: attrib.num_comp,
+                    atype: attrib.atype,
+                    stride: buffer.stride(),
+                    offset: attrib.offset,
+                    num_vtx: self.buffer2num_vtx[i_buffer],
+                })
+            })
+    }
+
+    /// the element buffer, its index type and the number of the indices
+    pub fn element_buffer(
+        &self,
+    ) -> Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)> {
+        self.ebo
+            .map(|ebo| (ebo, self.index_type, self.num_elem2vtx))
+    }
+
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            gl.enable(glow::DEPTH_TEST);
+            match (self.ebo.is_some(), self.num_instance) {
+                (true, 0) => gl.draw_elements(
+                    self.mode,
+                    self.num_elem2vtx as i32,
+                    self.index_type.gl_type(),
+                    0,
+                ),
+                (true, n) => gl.draw_elements_instanced(
+                    self.mode,
+                    self.num_elem2vtx as i32,
+                    self.index_type.gl_type(),
+                    0,
+                    n as i32,
+                ),
+                (false, 0) => gl.draw_arrays(self.mode, 0, self.num_vtx as i32),
+                (false, n) => gl.draw_arrays_instanced(self.mode, 0, self.num_vtx as i32, n as i32),
+            }
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(program) = self.program.take() {
+                program.destroy(gl);
+            }
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            for vbo in self.vbos.drain(..).flatten() {
+                gl.delete_buffer(vbo);
+            }
+            self.buffer2num_vtx.clear();
+            self.num_vtx = 0;
+            if let Some(ebo) = self.ebo.take() {
+                gl.delete_buffer(ebo);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_num_vtx_per_vertex_buffers() {
+        use crate::vertex_layout::{AttribType, VertexLayout};
+        let layout = VertexLayout::new()
+            .buffer()
+            .attrib("xyzIn", 3, AttribType::F32)
+            .buffer()
+            .attrib("nrmIn", 3, AttribType::F32)
+            .buffer()
+            .attrib("offsetIn", 3, AttribType::F32)
+            .divisor(1);
+        let mut drawer = super::Drawer::new(layout, glow::TRIANGLES);
+        assert_eq!(drawer.num_vtx_per_vertex_buffers(), None);
+        let vbo = |i: u32| Some(glow::NativeBuffer(std::num::NonZeroU32::new(i).unwrap()));
+        drawer.vbos = vec![vbo(1), None, vbo(3)];
+        drawer.buffer2num_vtx = vec![8, 0, 2];
+        // the per-instance buffer is not counted
+        assert_eq!(drawer.num_vtx_per_vertex_buffers(), Some(8));
+        drawer.vbos[1] = vbo(2);
+        drawer.buffer2num_vtx[1] = 5;
+        // the indices are checked against the shortest per-vertex buffer
+        assert_eq!(drawer.num_vtx_per_vertex_buffers(), Some(5));
+        assert!(crate::validate::max_index("elem2vtx", Some(5), 5).is_err());
+    }
+}
@@ -18,22 +18,30 @@ impl Drawer {
         }
     }
 
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        self.drawer_quad.compile_shader(gl);
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_quad.compile_shader(gl, builder);
     }
 
-    pub fn add_mesh2(&mut self, gl: &glow::Context, tri2vtx: &[usize], vtx2xy: &[f32]) {
+    pub fn add_mesh2(
+        &mut self,
+        gl: &glow::Context,
+        tri2vtx: &[usize],
+        vtx2xy: &[f32],
+    ) -> Result<(), String> {
         self.drawer_quad
-            .add_elem2vtx(gl, glow::TRIANGLES, tri2vtx, [0., 0., 0.]);
-        self.drawer_quad.set_vtx2xyz(gl, vtx2xy, 2);
+            .add_elem2vtx(gl, glow::TRIANGLES, tri2vtx, [0., 0., 0.])?;
+        self.drawer_quad.set_vtx2xyz(gl, vtx2xy, 2)
     }
 
     pub fn destroy(&self, gl: &glow::Context) {
         self.drawer_quad.destroy(gl);
     }
 
-    pub fn draw(&self, gl: &glow::Context, mvp: &[f32; 16]) {
-        let m = del_geo_core::mat4_col_major::from_identity();
-        self.drawer_quad.draw(gl, &m, mvp);
+    pub fn draw(&self, gl: &glow::Context) {
+        self.drawer_quad.draw(gl);
     }
 }
@@ -0,0 +1,434 @@
+//! draw quad and mixed polygon meshes
+//!
+//! The polygons are given in the compressed row storage (CSR) `elem2idx`, `idx2vtx`, where
+//! the vertices of the `i_elem`-th polygon are `idx2vtx[elem2idx[i_elem]..elem2idx[i_elem+1]]`.
+//! Each polygon is triangulated by the ear clipping on the plane of its normal for the fill, so
+//! the simple non-convex polygons are supported, while the outline is drawn along the original
+//! polygon edges. Each triangle remembers its polygon, so the fill can be colored per polygon
+//! and the polygon index can be rendered into an offscreen buffer for picking.
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+in vec3 xyzIn;
+in vec3 nrmIn;
+in vec3 rgbIn;
+in vec3 idIn;
+out vec3 nrm;
+out vec3 rgb;
+out vec3 posModel;
+flat out vec3 idColor;
+
+void main() {
+    gl_Position = frameMatPrj * frameMatView * vec4(xyzIn, 1.0);
+    posModel = xyzIn;
+    nrm = mat3(frameMatView) * nrmIn;
+    rgb = rgbIn;
+    idColor = idIn;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "normal_output"
+uniform bool isPick;
+uniform vec4 clipPlane; // the side `dot(clipPlane, vec4(p, 1)) > 0` is discarded
+in vec3 nrm;
+in vec3 rgb;
+in vec3 posModel;
+flat in vec3 idColor;
+layout (location = 0) out vec4 FragColor;
+
+void main() {
+    if( dot(clipPlane, vec4(posModel, 1.0)) > 0.0 ){ discard; }
+    if( isPick ){
+        FragColor = vec4(idColor, 1.0);
+        return;
+    }
+    float shade = 0.3 + 0.7 * abs(normalize(nrm).z);
+    FragColor = vec4(rgb * shade, 1.0);
+    write_normal(nrm);
+}
+"#;
+
+pub struct Drawer {
+    drawer_fill: crate::drawer_mesh::Drawer,
+    /// outlines of the polygons
+    pub drawer_edge: crate::drawer_elem2vtx_vtx2xyz::Drawer,
+    pub is_edge_visible: bool,
+    /// color of the polygons without the per-polygon color
+    pub color: [f32; 3],
+    /// polygon index of each triangle of the fill
+    tri2elem: Vec<usize>,
+    num_elem: usize,
+    /// see [`Drawer::set_clip_plane`]
+    clip_plane: Option<[f32; 4]>,
+    /// single-sampled buffer for [`Drawer::pick`]
+    target_pick: crate::render_target::RenderTarget,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        use crate::vertex_layout::{AttribType, VertexLayout};
+        let layout = VertexLayout::new()
+            .buffer()
+            .attrib("xyzIn", 3, AttribType::F32)
+            .attrib("nrmIn", 3, AttribType::F32)
+            .buffer()
+            .attrib("rgbIn", 3, AttribType::F32)
+            .buffer()
+            .attrib("idIn", 3, AttribType::U8Normalized);
+        Drawer {
+            drawer_fill: crate::drawer_mesh::Drawer::new(layout, glow::TRIANGLES),
+            drawer_edge: crate::drawer_elem2vtx_vtx2xyz::Drawer::new(),
+            is_edge_visible: true,
+            color: [0.8, 0.8, 0.8],
+            tri2elem: vec![],
+            num_elem: 0,
+            clip_plane: None,
+            target_pick: crate::render_target::RenderTarget::new(
+                &[crate::render_target::FORMAT_RGBA8],
+                true,
+            ),
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_fill
+            .compile_shader(gl, builder, VS_SRC, FS_SRC)
+            .unwrap();
+        self.drawer_edge.compile_shader(gl, builder);
+    }
+
+    /// replace the program of the fill (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        self.drawer_fill.set_program(gl, program);
+    }
+
+    pub fn set_quad2vtx_vtx2xyz(
+        &mut self,
+        gl: &glow::Context,
+        quad2vtx: &[usize],
+        vtx2xyz: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("quad2vtx", quad2vtx.len(), 4)?;
+        let elem2idx: Vec<usize> = (0..=quad2vtx.len() / 4).map(|i| i * 4).collect();
+        self.set_elem2idx_idx2vtx_vtx2xyz(gl, &elem2idx, quad2vtx, vtx2xyz)
+    }
+
+    /// set the polygon mesh in the CSR format. The per-polygon colors are reset to `color`.
+    /// Error if `elem2idx` does not start at 0, decreases, or does not end at `idx2vtx.len()`
+    pub fn set_elem2idx_idx2vtx_vtx2xyz(
+        &mut self,
+        gl: &glow::Context,
+        elem2idx: &[usize],
+        idx2vtx: &[usize],
+        vtx2xyz: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::offsets("elem2idx", elem2idx, idx2vtx.len())?;
+        crate::validate::index_range("idx2vtx", idx2vtx, vtx2xyz.len() / 3)?;
+        let num_elem = elem2idx.len().saturating_sub(1);
+        let xyz = |i_vtx: usize| arrayref::array_ref![vtx2xyz, i_vtx * 3, 3];
+        //
+        self.tri2elem.clear();
+        self.num_elem = num_elem;
+        let mut tri2node2xyznrm = Vec::<f32>::new();
+        let mut tri2node2id = Vec::<u8>::new();
+        let mut edge2vtx = Vec::<usize>::new();
+        let polygon_edges: std::collections::HashSet<(usize, usize)> = (0..num_elem)
+            .flat_map(|i_elem| {
+                let vtxs = &idx2vtx[elem2idx[i_elem]..elem2idx[i_elem + 1]];
+                (0..vtxs.len()).map(move |i| (vtxs[i], vtxs[(i + 1) % vtxs.len()]))
+            })
+            .collect();
+        for i_elem in 0..num_elem {
+            let vtxs = &idx2vtx[elem2idx[i_elem]..elem2idx[i_elem + 1]];
+            // Newell's method is robust for the non-planar quads
+            let mut nrm = [0f32; 3];
+            for (i, &i0_vtx) in vtxs.iter().enumerate() {
+                let p0 = xyz(i0_vtx);
+                let p1 = xyz(vtxs[(i + 1) % vtxs.len()]);
+                nrm[0] += (p0[1] - p1[1]) * (p0[2] + p1[2]);
+                nrm[1] += (p0[2] - p1[2]) * (p0[0] + p1[0]);
+                nrm[2] += (p0[0] - p1[0]) * (p0[1] + p1[1]);
+            }
+            let tris = ear_clipping(&project_polygon(vtxs, vtx2xyz, &nrm));
+            let nrm = del_geo_core::vec3::normalize(&nrm);
+            let id = encode_elem_id(i_elem);
+            for tri in tris {
+                for i_vtx in tri.map(|i| vtxs[i]) {
+                    tri2node2xyznrm.extend_from_slice(xyz(i_vtx));
+                    tri2node2xyznrm.extend_from_slice(&nrm);
+                    tri2node2id.extend_from_slice(&id);
+                }
+                self.tri2elem.push(i_elem);
+            }
+            for (i, &i0_vtx) in vtxs.iter().enumerate() {
+                let i1_vtx = vtxs[(i + 1) % vtxs.len()];
+                // the edge shared by two polygons is drawn once
+                if i0_vtx < i1_vtx || !polygon_edges.contains(&(i1_vtx, i0_vtx)) {
+                    edge2vtx.extend_from_slice(&[i0_vtx, i1_vtx]);
+                }
+            }
+        }
+        self.drawer_fill.set_buffer(gl, 0, &tri2node2xyznrm)?;
+        self.drawer_fill.set_buffer(gl, 2, &tri2node2id)?;
+        self.set_elem2rgb(gl, &vec![self.color; num_elem].concat())?;
+        //
+        self.drawer_edge.clear_elem2vtx(gl);
+        self.drawer_edge
+            .add_elem2vtx(gl, glow::LINES, &edge2vtx, [0., 0., 0.])?;
+        self.drawer_edge.set_vtx2xyz(gl, vtx2xyz, 3)?;
+        Ok(())
+    }
+
+    /// color of each polygon
+    pub fn set_elem2rgb(&mut self, gl: &glow::Context, elem2rgb: &[f32]) -> Result<(), String> {
+        crate::validate::length("elem2rgb", elem2rgb.len(), self.num_elem * 3)?;
+        let tri2node2rgb: Vec<f32> = self
+            .tri2elem
+            .iter()
+            .flat_map(|&i_elem| {
+                let rgb = arrayref::array_ref![elem2rgb, i_elem * 3, 3];
+                [*rgb; 3].concat()
+            })
+            .collect();
+        self.drawer_fill.set_buffer(gl, 1, &tri2node2rgb)
+    }
+
+    /// the triangles of the fill with the attributes `xyzIn` and `nrmIn` (not indexed)
+    pub fn drawer_fill(&self) -> &crate::drawer_mesh::Drawer {
+        &self.drawer_fill
+    }
+
+    /// polygon index of each triangle of the fill
+    pub fn tri2elem(&self) -> &[usize] {
+        &self.tri2elem
+    }
+
+    pub fn set_edge_color(&mut self, rgb: &[f32; 3]) {
+        self.drawer_edge.set_color(0, rgb);
+    }
+
+    /// plane `[a, b, c, d]` whose side `a*x + b*y + c*z + d > 0` of the fill and the outline
+    /// is discarded. `None` for no clipping
+    pub fn set_clip_plane(&mut self, clip_plane: Option<[f32; 4]>) {
+        self.clip_plane = clip_plane;
+        self.drawer_edge.clip_plane = clip_plane;
+    }
+
+    pub fn draw(&self, gl: &glow::Context) {
+        self.draw_fill(gl, false);
+        if self.is_edge_visible {
+            self.drawer_edge.draw(gl);
+        }
+    }
+
+    /// draw the polygon index encoded in RGB for the picking.
+    /// Decode the pixel read back with [`decode_elem_id`]. The framebuffer must not be
+    /// multisampled as the resolved color mixes the indices, so use [`Drawer::pick`] to draw it
+    /// into the single-sampled offscreen buffer
+    pub fn draw_elem_id(&self, gl: &glow::Context) {
+        self.draw_fill(gl, true);
+    }
+
+    /// index of the polygon under the pixel, or `None` for the background. `pix_coord` is in
+    /// pixels with the origin at the top-left corner of the current viewport
+    pub fn pick(&mut self, gl: &glow::Context, pix_coord: (f32, f32)) -> Option<usize> {
+        self.target_pick.begin(gl);
+        let (w, h) = self.target_pick.size();
+        let (x, y) = (
+            pix_coord.0.floor() as i32,
+            h - 1 - pix_coord.1.floor() as i32,
+        );
+        let mut rgba = [0u8; 4];
+        unsafe {
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            let is_blend = gl.is_enabled(glow::BLEND);
+            gl.enable(glow::DEPTH_TEST);
+            gl.disable(glow::BLEND);
+            gl.depth_mask(true);
+            gl.clear_buffer_f32_slice(glow::COLOR, 0, &[0.0; 4]);
+            gl.clear_buffer_f32_slice(glow::DEPTH, 0, &[1.0]);
+            self.draw_elem_id(gl);
+            if (0..w).contains(&x) && (0..h).contains(&y) {
+                gl.read_pixels(
+                    x,
+                    y,
+                    1,
+                    1,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelPackData::Slice(Some(&mut rgba)),
+                );
+            }
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            if is_blend {
+                gl.enable(glow::BLEND);
+            }
+        }
+        self.target_pick.end(gl);
+        decode_elem_id(&rgba)
+    }
+
+    fn draw_fill(&self, gl: &glow::Context, is_pick: bool) {
+        let Some(program) = self.drawer_fill.program() else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            program.set_bool(gl, "isPick", is_pick).unwrap();
+            let clip_plane = self.clip_plane.unwrap_or([0.; 4]);
+            program.set_vec4(gl, "clipPlane", &clip_plane).unwrap();
+            // push the fill behind the outline
+            gl.enable(glow::POLYGON_OFFSET_FILL);
+            gl.polygon_offset(1.0, 1.0);
+            self.drawer_fill.draw(gl);
+            gl.disable(glow::POLYGON_OFFSET_FILL);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.drawer_fill.destroy(gl);
+        self.drawer_edge.destroy(gl);
+        self.target_pick.destroy(gl);
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `i_elem + 1` in 24 bits, so that the black background decodes to `None`
+fn encode_elem_id(i_elem: usize) -> [u8; 3] {
+    let id = i_elem + 1;
+    [
+        (id & 0xff) as u8,
+        ((id >> 8) & 0xff) as u8,
+        ((id >> 16) & 0xff) as u8,
+    ]
+}
+
+/// polygon index from the pixel drawn by [`Drawer::draw_elem_id`]
+pub fn decode_elem_id(rgba: &[u8; 4]) -> Option<usize> {
+    let id = rgba[0] as usize | (rgba[1] as usize) << 8 | (rgba[2] as usize) << 16;
+    id.checked_sub(1)
+}
+
+/// 2D coordinates of the polygon on the plane perpendicular to the dominant axis of `nrm`,
+/// oriented so that the polygon is counter-clockwise if `nrm` is its normal
+fn project_polygon(vtxs: &[usize], vtx2xyz: &[f32], nrm: &[f32; 3]) -> Vec<[f32; 2]> {
+    let k = (0..3)
+        .max_by(|&a, &b| nrm[a].abs().total_cmp(&nrm[b].abs()))
+        .unwrap();
+    let (i0, i1) = if nrm[k] >= 0.0 {
+        ((k + 1) % 3, (k + 2) % 3)
+    } else {
+        ((k + 2) % 3, (k + 1) % 3)
+    };
+    vtxs.iter()
+        .map(|&i_vtx| [vtx2xyz[i_vtx * 3 + i0], vtx2xyz[i_vtx * 3 + i1]])
+        .collect()
+}
+
+/// triangulation of the simple counter-clockwise polygon by the ear clipping.
+/// Returns `xys.len() - 2` triangles of the local indices. For the degenerate or
+/// self-intersecting polygon, a vertex is clipped anyway so that it terminates
+fn ear_clipping(xys: &[[f32; 2]]) -> Vec<[usize; 3]> {
+    let area2 = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (xys[a], xys[b], xys[c]);
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    };
+    let mut idxs: Vec<usize> = (0..xys.len()).collect();
+    let mut tris = Vec::<[usize; 3]>::with_capacity(xys.len().saturating_sub(2));
+    while idxs.len() > 3 {
+        let n = idxs.len();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (idxs[(i + n - 1) % n], idxs[i], idxs[(i + 1) % n]);
+            if area2(a, b, c) <= 0.0 {
+                return false; // reflex or degenerate corner
+            }
+            // no other vertex inside or on the boundary of the triangle
+            idxs.iter().all(|&p| {
+                p == a
+                    || p == b
+                    || p == c
+                    || xys[p] == xys[a]
+                    || xys[p] == xys[b]
+                    || xys[p] == xys[c]
+                    || area2(a, b, p) < 0.0
+                    || area2(b, c, p) < 0.0
+                    || area2(c, a, p) < 0.0
+            })
+        };
+        let i = (0..n).find(|&i| is_ear(i)).unwrap_or(0);
+        tris.push([idxs[(i + n - 1) % n], idxs[i], idxs[(i + 1) % n]]);
+        idxs.remove(i);
+    }
+    if idxs.len() == 3 {
+        tris.push([idxs[0], idxs[1], idxs[2]]);
+    }
+    tris
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ear_clipping() {
+        let area = |xys: &[[f32; 2]], tri: &[usize; 3]| {
+            let (a, b, c) = (xys[tri[0]], xys[tri[1]], xys[tri[2]]);
+            0.5 * ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]))
+        };
+        // L-shape of the area 3, whose fan from the vertex 0 goes outside
+        let xys = [[1., 1.], [1., 2.], [0., 2.], [0., 0.], [2., 0.], [2., 1.]];
+        let tris = ear_clipping(&xys);
+        assert_eq!(tris.len(), xys.len() - 2);
+        for tri in &tris {
+            assert!(area(&xys, tri) > 0.0);
+        }
+        let sum: f32 = tris.iter().map(|tri| area(&xys, tri)).sum();
+        assert!((sum - 3.0).abs() < 1.0e-6);
+        //
+        assert_eq!(
+            ear_clipping(&[[0., 0.], [1., 0.], [0., 1.]]),
+            vec![[0, 1, 2]]
+        );
+        assert!(ear_clipping(&[[0., 0.], [1., 0.]]).is_empty());
+    }
+
+    #[test]
+    fn test_project_polygon() {
+        // square in the XZ-plane, counter-clockwise seen from +Y
+        let vtx2xyz = [0., 0., 0., 0., 0., 1., 1., 0., 1., 1., 0., 0.];
+        let xys = project_polygon(&[0, 1, 2, 3], &vtx2xyz, &[0., 1., 0.]);
+        assert_eq!(ear_clipping(&xys).len(), 2);
+        let tri = &ear_clipping(&xys)[0];
+        let (a, b, c) = (xys[tri[0]], xys[tri[1]], xys[tri[2]]);
+        assert!((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]) > 0.0);
+    }
+
+    #[test]
+    fn test_elem_id() {
+        for i_elem in [0, 1, 255, 256, 65536, (1 << 24) - 2] {
+            let id = encode_elem_id(i_elem);
+            assert_eq!(decode_elem_id(&[id[0], id[1], id[2], 255]), Some(i_elem));
+        }
+        assert_eq!(decode_elem_id(&[0, 0, 0, 0]), None);
+    }
+}
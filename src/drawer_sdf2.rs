@@ -0,0 +1,241 @@
+//! draw a scalar field sampled on a 2D grid (e.g., the signed distance field of a shape)
+//!
+//! The grid is drawn as a rectangle colored with a colormap. The zero level set and the
+//! isolines at a regular spacing are drawn as anti-aliased lines of a constant width in pixels.
+//! See [`crate::drawer_edge2`] for the explicit polylines.
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "vertex_id"
+uniform vec4 box; // min_x, min_y, max_x, max_y
+out vec2 uv;
+
+void main() {
+    uv = rectangle_strip_uv();
+    vec2 xy = mix(box.xy, box.zw, uv);
+    gl_Position = frameMatPrj * frameMatView * vec4(xy, 0.0, 1.0);
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "colormap"
+#include "antialias"
+uniform sampler2D gridTex;
+uniform int colormapIndex; // negative for no fill
+uniform vec2 valueRange;
+uniform vec3 contourColor;
+uniform float contourWidth;
+uniform vec3 isolineColor;
+uniform float isolineWidth;
+uniform float isolineSpacing; // zero for no isolines
+in vec2 uv;
+out vec4 FragColor;
+
+void main() {
+    float v = texture(gridTex, uv).r;
+    vec4 c = vec4(0.0);
+    if( colormapIndex >= 0 ){
+        float t = (v - valueRange.x) / (valueRange.y - valueRange.x);
+        c = vec4(colormap(colormapIndex, t), 1.0);
+    }
+    if( isolineSpacing > 0.0 ){
+        float a = isoline_coverage(v, isolineSpacing, isolineWidth);
+        c = mix(c, vec4(isolineColor, 1.0), a);
+    }
+    {
+        float a = line_coverage(abs(v) / fwidth(v), contourWidth);
+        c = mix(c, vec4(contourColor, 1.0), a);
+    }
+    if( c.a == 0.0 ){ discard; }
+    FragColor = c;
+}
+"#;
+
+pub struct Drawer {
+    /// the grid occupies this rectangle `[min_x, min_y, max_x, max_y]`
+    pub aabb: [f32; 4],
+    /// `None` for the lines only
+    pub colormap: Option<crate::colormap::Colormap>,
+    /// values mapped to [0,1] of the colormap
+    pub value_range: [f32; 2],
+    /// color and width in pixels of the zero level set
+    pub contour: ([f32; 3], f32),
+    /// color and width in pixels of the isolines
+    pub isoline: ([f32; 3], f32),
+    /// interval of the values between the isolines. `None` for no isolines
+    pub isoline_spacing: Option<f32>,
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    tex_grid: Option<glow::NativeTexture>,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            aabb: [0.0, 0.0, 1.0, 1.0],
+            colormap: Some(crate::colormap::Colormap::Coolwarm),
+            value_range: [-1.0, 1.0],
+            contour: ([0.0, 0.0, 0.0], 2.0),
+            isoline: ([0.3, 0.3, 0.3], 1.0),
+            isoline_spacing: None,
+            program: None,
+            vertex_array: None,
+            tex_grid: None,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        self.set_program(gl, program);
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// upload the grid where `data[j * dims[0] + i]` is the value at `(i, j)`. The values are
+    /// sampled at the centers of the cells. `value_range` is set to `[-m, m]` with the maximum
+    /// absolute value `m`, so that the zero is at the center of the colormap
+    pub fn set_grid(
+        &mut self,
+        gl: &glow::Context,
+        dims: [usize; 2],
+        data: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::length("grid", data.len(), dims[0] * dims[1])?;
+        let m = data.iter().fold(0f32, |m, v| m.max(v.abs()));
+        self.value_range = if m > 0.0 { [-m, m] } else { [-1.0, 1.0] };
+        unsafe {
+            let tex = *self
+                .tex_grid
+                .get_or_insert_with(|| gl.create_texture().unwrap());
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::R32F as i32,
+                dims[0] as i32,
+                dims[1] as i32,
+                0,
+                glow::RED,
+                glow::FLOAT,
+                glow::PixelUnpackData::Slice(Some(bytemuck::cast_slice(data))),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            // the contours are blocky on the target without the linear filtering of R32F
+            let filter = if crate::render_target::is_f32_linear_filterable(gl) {
+                glow::LINEAR
+            } else {
+                glow::NEAREST
+            };
+            for (param, value) in [
+                (glow::TEXTURE_MIN_FILTER, filter),
+                (glow::TEXTURE_MAG_FILTER, filter),
+                (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+                (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, param, value as i32);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Ok(())
+    }
+
+    /// draw with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        let (Some(program), Some(tex_grid)) = (&self.program, self.tex_grid) else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex_grid));
+            program.set_i32(gl, "gridTex", 0).unwrap();
+            program.set_vec4(gl, "box", &self.aabb).unwrap();
+            program
+                .set_i32(
+                    gl,
+                    "colormapIndex",
+                    self.colormap.map_or(-1, |c| c.glsl_index()),
+                )
+                .unwrap();
+            program
+                .set_vec2(gl, "valueRange", &self.value_range)
+                .unwrap();
+            program
+                .set_vec3(gl, "contourColor", &self.contour.0)
+                .unwrap();
+            program
+                .set_f32(gl, "contourWidth", &[self.contour.1])
+                .unwrap();
+            program
+                .set_vec3(gl, "isolineColor", &self.isoline.0)
+                .unwrap();
+            program
+                .set_f32(gl, "isolineWidth", &[self.isoline.1])
+                .unwrap();
+            program
+                .set_f32(gl, "isolineSpacing", &[self.isoline_spacing.unwrap_or(0.0)])
+                .unwrap();
+            // the lines are blended over the background if there is no fill.
+            // The blending of the caller is restored at the end
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let blend_func = [
+                glow::BLEND_SRC_RGB,
+                glow::BLEND_DST_RGB,
+                glow::BLEND_SRC_ALPHA,
+                glow::BLEND_DST_ALPHA,
+            ]
+            .map(|param| gl.get_parameter_i32(param) as u32);
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func;
+            gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+            if !is_blend {
+                gl.disable(glow::BLEND);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(program) = self.program.take() {
+                program.destroy(gl);
+            }
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            if let Some(tex) = self.tex_grid.take() {
+                gl.delete_texture(tex);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,350 @@
+//! draw silhouette, crease and boundary edges of a triangle mesh as lines with a width in pixels
+//!
+//! The edges are extracted from `tri2vtx` with their adjacent triangles. Whether an edge is
+//! on the silhouette is decided in the vertex shader, so the lines follow the camera motion
+//! without re-uploading. Draw this after the mesh so that the lines are depth tested against it.
+
+use glow::HasContext;
+
+/// bit flags for [`Drawer::edge_mask`]
+pub const SILHOUETTE: i32 = 1;
+pub const CREASE: i32 = 2;
+pub const BOUNDARY: i32 = 4;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform float lineWidth;
+uniform float cosCrease;
+uniform int edgeMask;
+uniform float depthBias;
+
+layout (location = 0) in vec3 p0;
+layout (location = 1) in vec3 p1;
+layout (location = 2) in vec3 n0;
+layout (location = 3) in vec3 n1;
+layout (location = 4) in vec2 corner; // (side in {-1,1}, end in {0,1})
+
+void main() {
+    mat4 matMV = frameMatView;
+    mat4 matPrj = frameMatPrj;
+    vec2 viewportSize = frameViewportTime.xy;
+    vec4 q0 = matMV * vec4(p0, 1.0);
+    vec4 q1 = matMV * vec4(p1, 1.0);
+    bool is_draw = false;
+    if( dot(n1, n1) == 0.0 ){
+        is_draw = (edgeMask & 4) != 0;
+    }
+    else {
+        // the last row of the orthographic projection is (0,0,0,1)
+        vec3 to_eye = matPrj[3][3] > 0.5 ? vec3(0.0, 0.0, 1.0) : -0.5 * (q0.xyz + q1.xyz);
+        float d0 = dot(mat3(matMV) * n0, to_eye);
+        float d1 = dot(mat3(matMV) * n1, to_eye);
+        if( (edgeMask & 1) != 0 && d0 * d1 <= 0.0 ){ is_draw = true; }
+        if( (edgeMask & 2) != 0 && dot(normalize(n0), normalize(n1)) < cosCrease ){ is_draw = true; }
+    }
+    if( !is_draw ){
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0); // degenerated outside of the clip volume
+        return;
+    }
+    vec4 c0 = matPrj * q0;
+    vec4 c1 = matPrj * q1;
+    vec2 dir = (c1.xy / c1.w - c0.xy / c0.w) * viewportSize;
+    dir = length(dir) > 0.0 ? normalize(dir) : vec2(1.0, 0.0);
+    vec2 perp = vec2(-dir.y, dir.x);
+    vec4 c = corner.y < 0.5 ? c0 : c1;
+    c.xy += perp * corner.x * lineWidth / viewportSize * c.w;
+    c.z -= depthBias * c.w;
+    gl_Position = c;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+uniform vec3 color;
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(color, 1.0);
+}
+"#;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbo: Option<glow::NativeBuffer>,
+    ebo: Option<glow::NativeBuffer>,
+    num_edge: usize,
+    pub color: [f32; 3],
+    /// width of the line in pixels
+    pub line_width: f32,
+    /// an edge is a crease if the angle between the adjacent face normals is larger than this
+    pub crease_angle_deg: f32,
+    /// combination of [`SILHOUETTE`], [`CREASE`] and [`BOUNDARY`]
+    pub edge_mask: i32,
+    /// offset toward the camera in NDC to avoid z-fighting with the mesh
+    pub depth_bias: f32,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            vertex_array: None,
+            vbo: None,
+            ebo: None,
+            num_edge: 0,
+            color: [0., 0., 0.],
+            line_width: 2.0,
+            crease_angle_deg: 30.0,
+            edge_mask: SILHOUETTE | CREASE | BOUNDARY,
+            depth_bias: 1.0e-4,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        unsafe {
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            let vao0 = gl.create_vertex_array().unwrap();
+            self.vertex_array = Some(vao0);
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// extract the edges of the triangle mesh and upload them as quads.
+    /// Error if the shapes of the arrays are invalid or an index is out of range
+    pub fn set_tri2vtx_vtx2xyz<T>(
+        &mut self,
+        gl: &glow::Context,
+        tri2vtx: &[T],
+        vtx2xyz: &[f32],
+    ) -> Result<(), String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<usize>,
+    {
+        crate::validate::multiple_of("tri2vtx", tri2vtx.len(), 3)?;
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::index_range("tri2vtx", tri2vtx, vtx2xyz.len() / 3)?;
+        let num_tri = tri2vtx.len() / 3;
+        let tri2nrm: Vec<[f32; 3]> = (0..num_tri)
+            .map(|i_tri| {
+                let p = |i_node: usize| {
+                    let i_vtx: usize = tri2vtx[i_tri * 3 + i_node].as_();
+                    arrayref::array_ref![vtx2xyz, i_vtx * 3, 3]
+                };
+                del_geo_core::tri3::unit_normal_area(p(0), p(1), p(2)).0
+            })
+            .collect();
+        let edge2tri = edge2tri_from_tri2vtx(tri2vtx);
+        //
+        let mut quad2data = Vec::<f32>::with_capacity(edge2tri.len() * 4 * 14);
+        let mut quad2idx = Vec::<u32>::with_capacity(edge2tri.len() * 6);
+        for (i_edge, &((i0, i1), tris)) in edge2tri.iter().enumerate() {
+            let p0 = arrayref::array_ref![vtx2xyz, i0 * 3, 3];
+            let p1 = arrayref::array_ref![vtx2xyz, i1 * 3, 3];
+            // a zero normal marks the boundary (or non-manifold) edge
+            let (n0, n1) = match tris {
+                [Some(t0), Some(t1)] => (tri2nrm[t0], tri2nrm[t1]),
+                [Some(t0), None] => (tri2nrm[t0], [0.; 3]),
+                _ => ([0.; 3], [0.; 3]),
+            };
+            for corner in [[-1., 0.], [1., 0.], [1., 1.], [-1., 1.]] {
+                quad2data.extend_from_slice(p0);
+                quad2data.extend_from_slice(p1);
+                quad2data.extend_from_slice(&n0);
+                quad2data.extend_from_slice(&n1);
+                quad2data.extend_from_slice(&corner);
+            }
+            let i = (i_edge * 4) as u32;
+            quad2idx.extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+        }
+        self.num_edge = edge2tri.len();
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            if let Some(vbo) = self.vbo.take() {
+                gl.delete_buffer(vbo);
+            }
+            if let Some(ebo) = self.ebo.take() {
+                gl.delete_buffer(ebo);
+            }
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&quad2data),
+                glow::STATIC_DRAW,
+            );
+            let stride = (14 * std::mem::size_of::<f32>()) as i32;
+            for (loc, size, offset) in [(0, 3, 0), (1, 3, 3), (2, 3, 6), (3, 3, 9), (4, 2, 12)] {
+                gl.enable_vertex_attrib_array(loc);
+                gl.vertex_attrib_pointer_f32(
+                    loc,
+                    size,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset * std::mem::size_of::<f32>() as i32,
+                );
+            }
+            let ebo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                bytemuck::cast_slice(&quad2idx),
+                glow::STATIC_DRAW,
+            );
+            gl.bind_vertex_array(None);
+            self.vbo = Some(vbo);
+            self.ebo = Some(ebo);
+        }
+        Ok(())
+    }
+
+    /// draw with the camera and the viewport size of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        let cos_crease = self.crease_angle_deg.to_radians().cos();
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            gl.enable(glow::DEPTH_TEST);
+            program
+                .set_f32(gl, "lineWidth", &[self.line_width])
+                .unwrap();
+            program.set_f32(gl, "cosCrease", &[cos_crease]).unwrap();
+            program.set_i32(gl, "edgeMask", self.edge_mask).unwrap();
+            program
+                .set_f32(gl, "depthBias", &[self.depth_bias])
+                .unwrap();
+            program.set_vec3(gl, "color", &self.color).unwrap();
+            gl.draw_elements(
+                glow::TRIANGLES,
+                (self.num_edge * 6) as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            self.program.as_ref().unwrap().destroy(gl);
+            gl.delete_vertex_array(self.vertex_array.unwrap());
+            if let Some(vbo) = self.vbo {
+                gl.delete_buffer(vbo);
+            }
+            if let Some(ebo) = self.ebo {
+                gl.delete_buffer(ebo);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// unique edges `(i0, i1)` with `i0 < i1` and their adjacent triangles.
+/// Both triangles are `None` if the edge is shared by more than two triangles
+#[allow(clippy::type_complexity)]
+fn edge2tri_from_tri2vtx<T>(tri2vtx: &[T]) -> Vec<((usize, usize), [Option<usize>; 2])>
+where
+    T: 'static + Copy + num_traits::AsPrimitive<usize>,
+{
+    let mut map = std::collections::BTreeMap::<(usize, usize), [Option<usize>; 2]>::new();
+    let mut non_manifold = std::collections::BTreeSet::<(usize, usize)>::new();
+    for (i_tri, node2vtx) in tri2vtx.chunks(3).enumerate() {
+        for i_node in 0..3 {
+            let i0: usize = node2vtx[i_node].as_();
+            let i1: usize = node2vtx[(i_node + 1) % 3].as_();
+            let key = (i0.min(i1), i0.max(i1));
+            let tris = map.entry(key).or_insert([None, None]);
+            match tris {
+                [None, _] => tris[0] = Some(i_tri),
+                [Some(_), None] => tris[1] = Some(i_tri),
+                _ => {
+                    non_manifold.insert(key);
+                }
+            }
+        }
+    }
+    for key in non_manifold {
+        map.insert(key, [None, None]);
+    }
+    map.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edge2tri_from_tri2vtx;
+
+    #[test]
+    fn test_edge2tri_closed() {
+        // tetrahedron: every edge is shared by exactly two triangles
+        let tri2vtx: [usize; 12] = [0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2];
+        let edge2tri = edge2tri_from_tri2vtx(&tri2vtx);
+        assert_eq!(edge2tri.len(), 6);
+        for ((i0, i1), tris) in edge2tri {
+            assert!(i0 < i1);
+            let [Some(t0), Some(t1)] = tris else {
+                panic!("edge ({i0}, {i1}) is not shared by two triangles");
+            };
+            assert_ne!(t0, t1);
+            for t in [t0, t1] {
+                assert!(tri2vtx[t * 3..t * 3 + 3].contains(&i0));
+                assert!(tri2vtx[t * 3..t * 3 + 3].contains(&i1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge2tri_boundary() {
+        // two triangles sharing the diagonal of a quad
+        let tri2vtx: [u32; 6] = [0, 1, 2, 0, 2, 3];
+        let edge2tri = edge2tri_from_tri2vtx(&tri2vtx);
+        assert_eq!(
+            edge2tri,
+            vec![
+                ((0, 1), [Some(0), None]),
+                ((0, 2), [Some(0), Some(1)]),
+                ((0, 3), [Some(1), None]),
+                ((1, 2), [Some(0), None]),
+                ((2, 3), [Some(1), None]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edge2tri_non_manifold() {
+        // three triangles sharing the edge (0, 1)
+        let tri2vtx: [usize; 9] = [0, 1, 2, 1, 0, 3, 0, 1, 4];
+        let edge2tri = edge2tri_from_tri2vtx(&tri2vtx);
+        assert_eq!(edge2tri.len(), 7);
+        for ((i0, i1), tris) in edge2tri {
+            if (i0, i1) == (0, 1) {
+                assert_eq!(tris, [None, None]);
+            } else {
+                assert!(matches!(tris, [Some(_), None]));
+            }
+        }
+    }
+}
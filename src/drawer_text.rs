@@ -0,0 +1,519 @@
+//! draw text labels with a built-in 5x7 bitmap font
+//!
+//! The labels are anchored at 3D positions in the world coordinate (billboarded and of fixed
+//! pixel size, optionally depth tested), at NDC positions, or at pixel positions of the viewport.
+//! All the labels are batched into one vertex buffer and drawn with a single call.
+
+use glow::HasContext;
+
+/// where a label is placed. The anchor is at the left end of the text and vertically centered
+pub enum Anchor {
+    /// position in the world coordinate transformed by the modelview and the projection
+    World([f32; 3]),
+    /// position in the normalized device coordinate
+    Ndc([f32; 2]),
+    /// position in pixels with the origin at the top-left corner of the viewport and y down
+    Pixel([f32; 2]),
+}
+
+const NUM_FLOAT_PER_VTX: usize = 11;
+const CELL_W: usize = 8;
+const CELL_H: usize = 8;
+const ATLAS_NCOL: usize = 16;
+const ATLAS_NROW: usize = 6;
+/// horizontal advance of a glyph in font texels
+const ADVANCE: f32 = 6.0;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform float depthBias;
+uniform float scale;
+
+layout (location = 0) in vec3 anchor;
+layout (location = 1) in float space; // 0: world, 1: NDC, 2: pixel
+layout (location = 2) in vec2 offset; // in font texels
+layout (location = 3) in vec2 uvIn;
+layout (location = 4) in vec3 rgbIn;
+out vec2 uv;
+out vec3 v_color;
+
+void main() {
+    vec2 viewportSize = frameViewportTime.xy;
+    vec4 c;
+    if( space < 0.5 ){
+        c = frameMatPrj * frameMatView * vec4(anchor, 1.0);
+        c.z -= depthBias * c.w;
+    }
+    else if( space < 1.5 ){
+        c = vec4(anchor.xy, -0.999, 1.0);
+    }
+    else {
+        c = vec4(anchor.x / viewportSize.x * 2.0 - 1.0, 1.0 - anchor.y / viewportSize.y * 2.0, -0.999, 1.0);
+    }
+    c.xy += offset * scale * 2.0 / viewportSize * c.w;
+    gl_Position = c;
+    uv = uvIn;
+    v_color = rgbIn;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+uniform sampler2D font;
+in vec2 uv;
+in vec3 v_color;
+out vec4 FragColor;
+
+void main() {
+    if( texture(font, uv).r < 0.5 ){ discard; }
+    FragColor = vec4(v_color, 1.0);
+}
+"#;
+
+pub struct Drawer {
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbo: Option<glow::NativeBuffer>,
+    ebo: Option<glow::NativeBuffer>,
+    texture: Option<glow::NativeTexture>,
+    /// size of a font texel in pixels
+    pub scale: f32,
+    /// the labels at world positions are occluded by the meshes if true
+    pub is_depth_test: bool,
+    /// offset toward the camera in NDC for the labels at world positions
+    pub depth_bias: f32,
+    glyph2vtxdata: Vec<f32>,
+    num_glyph_uploaded: usize,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            vertex_array: None,
+            vbo: None,
+            ebo: None,
+            texture: None,
+            scale: 2.0,
+            is_depth_test: true,
+            depth_bias: 1.0e-3,
+            glyph2vtxdata: vec![],
+            num_glyph_uploaded: 0,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        unsafe {
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            //
+            let atlas = font_atlas();
+            let tex = gl.create_texture().unwrap();
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::R8 as i32,
+                (ATLAS_NCOL * CELL_W) as i32,
+                (ATLAS_NROW * CELL_H) as i32,
+                0,
+                glow::RED,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(Some(&atlas)),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            for param in [glow::TEXTURE_MIN_FILTER, glow::TEXTURE_MAG_FILTER] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, param, glow::NEAREST as i32);
+            }
+            for param in [glow::TEXTURE_WRAP_S, glow::TEXTURE_WRAP_T] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, param, glow::CLAMP_TO_EDGE as i32);
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            self.texture = Some(tex);
+            //
+            let vao0 = gl.create_vertex_array().unwrap();
+            gl.bind_vertex_array(Some(vao0));
+            let vbo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            let stride = (NUM_FLOAT_PER_VTX * std::mem::size_of::<f32>()) as i32;
+            for (loc, size, offset) in [(0, 3, 0), (1, 1, 3), (2, 2, 4), (3, 2, 6), (4, 3, 8)] {
+                gl.enable_vertex_attrib_array(loc);
+                gl.vertex_attrib_pointer_f32(
+                    loc,
+                    size,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset * std::mem::size_of::<f32>() as i32,
+                );
+            }
+            let ebo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+            gl.bind_vertex_array(None);
+            self.vertex_array = Some(vao0);
+            self.vbo = Some(vbo);
+            self.ebo = Some(ebo);
+        }
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// remove all the labels. Call [`Drawer::update`] to reflect it
+    pub fn clear(&mut self) {
+        self.glyph2vtxdata.clear();
+    }
+
+    /// add a label. Multiple lines can be separated by `'\n'`.
+    /// The characters outside of the printable ASCII are drawn as `'?'`
+    pub fn add_label(&mut self, anchor: Anchor, text: &str, rgb: [f32; 3]) {
+        let (xyz, space) = match anchor {
+            Anchor::World(p) => (p, 0.),
+            Anchor::Ndc(p) => ([p[0], p[1], 0.], 1.),
+            Anchor::Pixel(p) => ([p[0], p[1], 0.], 2.),
+        };
+        let num_line = text.lines().count().max(1);
+        let line_height = CELL_H as f32 + 1.0;
+        let top = 0.5 * num_line as f32 * line_height;
+        for (i_line, line) in text.lines().enumerate() {
+            let y1 = top - i_line as f32 * line_height;
+            let y0 = y1 - CELL_H as f32;
+            for (i_char, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                let c = if (' '..='~').contains(&c) { c } else { '?' };
+                let code = c as usize - 32;
+                let (col, row) = (code % ATLAS_NCOL, code / ATLAS_NCOL);
+                let u0 = (col * CELL_W) as f32 / (ATLAS_NCOL * CELL_W) as f32;
+                let u1 = (col * CELL_W) as f32 / (ATLAS_NCOL * CELL_W) as f32
+                    + ADVANCE / (ATLAS_NCOL * CELL_W) as f32;
+                let v0 = (row * CELL_H) as f32 / (ATLAS_NROW * CELL_H) as f32;
+                let v1 = ((row + 1) * CELL_H) as f32 / (ATLAS_NROW * CELL_H) as f32;
+                let x0 = i_char as f32 * ADVANCE;
+                let x1 = x0 + ADVANCE;
+                // the first row of the atlas is the top of the glyph
+                for (x, y, u, v) in [
+                    (x0, y0, u0, v1),
+                    (x1, y0, u1, v1),
+                    (x1, y1, u1, v0),
+                    (x0, y1, u0, v0),
+                ] {
+                    self.glyph2vtxdata.extend_from_slice(&xyz);
+                    self.glyph2vtxdata.extend_from_slice(&[space, x, y, u, v]);
+                    self.glyph2vtxdata.extend_from_slice(&rgb);
+                }
+            }
+        }
+    }
+
+    /// number of the glyphs of the labels added so far (the spaces are not counted)
+    pub fn num_glyph(&self) -> usize {
+        self.glyph2vtxdata.len() / (NUM_FLOAT_PER_VTX * 4)
+    }
+
+    /// upload the labels added so far
+    pub fn update(&mut self, gl: &glow::Context) {
+        let num_glyph = self.num_glyph();
+        let glyph2idx = glyph2idx(num_glyph);
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            gl.bind_buffer(glow::ARRAY_BUFFER, self.vbo);
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                bytemuck::cast_slice(&self.glyph2vtxdata),
+                glow::DYNAMIC_DRAW,
+            );
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                bytemuck::cast_slice(&glyph2idx),
+                glow::DYNAMIC_DRAW,
+            );
+            gl.bind_vertex_array(None);
+        }
+        self.num_glyph_uploaded = num_glyph;
+    }
+
+    /// draw with the camera and the viewport size of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        if self.num_glyph_uploaded == 0 {
+            return;
+        }
+        unsafe {
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            if self.is_depth_test {
+                gl.enable(glow::DEPTH_TEST);
+            } else {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, self.texture);
+            program.set_i32(gl, "font", 0).unwrap();
+            program
+                .set_f32(gl, "depthBias", &[self.depth_bias])
+                .unwrap();
+            program.set_f32(gl, "scale", &[self.scale]).unwrap();
+            gl.draw_elements(
+                glow::TRIANGLES,
+                (self.num_glyph_uploaded * 6) as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+            gl.bind_vertex_array(None);
+            if is_depth_test {
+                gl.enable(glow::DEPTH_TEST);
+            } else {
+                gl.disable(glow::DEPTH_TEST);
+            }
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            self.program.as_ref().unwrap().destroy(gl);
+            gl.delete_vertex_array(self.vertex_array.unwrap());
+            gl.delete_buffer(self.vbo.unwrap());
+            gl.delete_buffer(self.ebo.unwrap());
+            gl.delete_texture(self.texture.unwrap());
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// two triangles of each quad of the four vertices of a glyph
+fn glyph2idx(num_glyph: usize) -> Vec<u32> {
+    (0..num_glyph as u32)
+        .flat_map(|i| {
+            let i = i * 4;
+            [i, i + 1, i + 2, i, i + 2, i + 3]
+        })
+        .collect()
+}
+
+/// rasterize [`FONT_5X7`] into the 16x6 grid of 8x8 cells (one byte per texel)
+fn font_atlas() -> Vec<u8> {
+    let width = ATLAS_NCOL * CELL_W;
+    let mut atlas = vec![0u8; width * ATLAS_NROW * CELL_H];
+    for (code, columns) in FONT_5X7.chunks(5).enumerate() {
+        let (col, row) = (code % ATLAS_NCOL, code / ATLAS_NCOL);
+        for (ix, bits) in columns.iter().enumerate() {
+            for iy in 0..CELL_H {
+                if (bits >> iy) & 1 == 1 {
+                    atlas[(row * CELL_H + iy) * width + col * CELL_W + ix] = 255;
+                }
+            }
+        }
+    }
+    atlas
+}
+
+/// ASCII from `' '` to `'~'`. Each glyph is five columns from the left and the bit `i`
+/// of a column is the `i`-th row from the top
+#[rustfmt::skip]
+const FONT_5X7: [u8; 95 * 5] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x00, 0x5F, 0x00, 0x00, // '!'
+    0x00, 0x07, 0x00, 0x07, 0x00, // '"'
+    0x14, 0x7F, 0x14, 0x7F, 0x14, // '#'
+    0x24, 0x2A, 0x7F, 0x2A, 0x12, // '$'
+    0x23, 0x13, 0x08, 0x64, 0x62, // '%'
+    0x36, 0x49, 0x56, 0x20, 0x50, // '&'
+    0x00, 0x08, 0x07, 0x03, 0x00, // '''
+    0x00, 0x1C, 0x22, 0x41, 0x00, // '('
+    0x00, 0x41, 0x22, 0x1C, 0x00, // ')'
+    0x2A, 0x1C, 0x7F, 0x1C, 0x2A, // '*'
+    0x08, 0x08, 0x3E, 0x08, 0x08, // '+'
+    0x00, 0x80, 0x70, 0x30, 0x00, // ','
+    0x08, 0x08, 0x08, 0x08, 0x08, // '-'
+    0x00, 0x00, 0x60, 0x60, 0x00, // '.'
+    0x20, 0x10, 0x08, 0x04, 0x02, // '/'
+    0x3E, 0x51, 0x49, 0x45, 0x3E, // '0'
+    0x00, 0x42, 0x7F, 0x40, 0x00, // '1'
+    0x72, 0x49, 0x49, 0x49, 0x46, // '2'
+    0x21, 0x41, 0x49, 0x4D, 0x33, // '3'
+    0x18, 0x14, 0x12, 0x7F, 0x10, // '4'
+    0x27, 0x45, 0x45, 0x45, 0x39, // '5'
+    0x3C, 0x4A, 0x49, 0x49, 0x31, // '6'
+    0x41, 0x21, 0x11, 0x09, 0x07, // '7'
+    0x36, 0x49, 0x49, 0x49, 0x36, // '8'
+    0x46, 0x49, 0x49, 0x29, 0x1E, // '9'
+    0x00, 0x00, 0x14, 0x00, 0x00, // ':'
+    0x00, 0x40, 0x34, 0x00, 0x00, // ';'
+    0x00, 0x08, 0x14, 0x22, 0x41, // '<'
+    0x14, 0x14, 0x14, 0x14, 0x14, // '='
+    0x00, 0x41, 0x22, 0x14, 0x08, // '>'
+    0x02, 0x01, 0x59, 0x09, 0x06, // '?'
+    0x3E, 0x41, 0x5D, 0x59, 0x4E, // '@'
+    0x7C, 0x12, 0x11, 0x12, 0x7C, // 'A'
+    0x7F, 0x49, 0x49, 0x49, 0x36, // 'B'
+    0x3E, 0x41, 0x41, 0x41, 0x22, // 'C'
+    0x7F, 0x41, 0x41, 0x41, 0x3E, // 'D'
+    0x7F, 0x49, 0x49, 0x49, 0x41, // 'E'
+    0x7F, 0x09, 0x09, 0x09, 0x01, // 'F'
+    0x3E, 0x41, 0x41, 0x51, 0x73, // 'G'
+    0x7F, 0x08, 0x08, 0x08, 0x7F, // 'H'
+    0x00, 0x41, 0x7F, 0x41, 0x00, // 'I'
+    0x20, 0x40, 0x41, 0x3F, 0x01, // 'J'
+    0x7F, 0x08, 0x14, 0x22, 0x41, // 'K'
+    0x7F, 0x40, 0x40, 0x40, 0x40, // 'L'
+    0x7F, 0x02, 0x1C, 0x02, 0x7F, // 'M'
+    0x7F, 0x04, 0x08, 0x10, 0x7F, // 'N'
+    0x3E, 0x41, 0x41, 0x41, 0x3E, // 'O'
+    0x7F, 0x09, 0x09, 0x09, 0x06, // 'P'
+    0x3E, 0x41, 0x51, 0x21, 0x5E, // 'Q'
+    0x7F, 0x09, 0x19, 0x29, 0x46, // 'R'
+    0x26, 0x49, 0x49, 0x49, 0x32, // 'S'
+    0x03, 0x01, 0x7F, 0x01, 0x03, // 'T'
+    0x3F, 0x40, 0x40, 0x40, 0x3F, // 'U'
+    0x1F, 0x20, 0x40, 0x20, 0x1F, // 'V'
+    0x3F, 0x40, 0x38, 0x40, 0x3F, // 'W'
+    0x63, 0x14, 0x08, 0x14, 0x63, // 'X'
+    0x03, 0x04, 0x78, 0x04, 0x03, // 'Y'
+    0x61, 0x59, 0x49, 0x4D, 0x43, // 'Z'
+    0x00, 0x7F, 0x41, 0x41, 0x41, // '['
+    0x02, 0x04, 0x08, 0x10, 0x20, // '\'
+    0x00, 0x41, 0x41, 0x41, 0x7F, // ']'
+    0x04, 0x02, 0x01, 0x02, 0x04, // '^'
+    0x40, 0x40, 0x40, 0x40, 0x40, // '_'
+    0x00, 0x03, 0x07, 0x08, 0x00, // '`'
+    0x20, 0x54, 0x54, 0x78, 0x40, // 'a'
+    0x7F, 0x28, 0x44, 0x44, 0x38, // 'b'
+    0x38, 0x44, 0x44, 0x44, 0x28, // 'c'
+    0x38, 0x44, 0x44, 0x28, 0x7F, // 'd'
+    0x38, 0x54, 0x54, 0x54, 0x18, // 'e'
+    0x00, 0x08, 0x7E, 0x09, 0x02, // 'f'
+    0x18, 0xA4, 0xA4, 0x9C, 0x78, // 'g'
+    0x7F, 0x08, 0x04, 0x04, 0x78, // 'h'
+    0x00, 0x44, 0x7D, 0x40, 0x00, // 'i'
+    0x20, 0x40, 0x40, 0x3D, 0x00, // 'j'
+    0x7F, 0x10, 0x28, 0x44, 0x00, // 'k'
+    0x00, 0x41, 0x7F, 0x40, 0x00, // 'l'
+    0x7C, 0x04, 0x78, 0x04, 0x78, // 'm'
+    0x7C, 0x08, 0x04, 0x04, 0x78, // 'n'
+    0x38, 0x44, 0x44, 0x44, 0x38, // 'o'
+    0xFC, 0x18, 0x24, 0x24, 0x18, // 'p'
+    0x18, 0x24, 0x24, 0x18, 0xFC, // 'q'
+    0x7C, 0x08, 0x04, 0x04, 0x08, // 'r'
+    0x48, 0x54, 0x54, 0x54, 0x24, // 's'
+    0x04, 0x04, 0x3F, 0x44, 0x24, // 't'
+    0x3C, 0x40, 0x40, 0x20, 0x7C, // 'u'
+    0x1C, 0x20, 0x40, 0x20, 0x1C, // 'v'
+    0x3C, 0x40, 0x30, 0x40, 0x3C, // 'w'
+    0x44, 0x28, 0x10, 0x28, 0x44, // 'x'
+    0x4C, 0x90, 0x90, 0x90, 0x7C, // 'y'
+    0x44, 0x64, 0x54, 0x4C, 0x44, // 'z'
+    0x00, 0x08, 0x36, 0x41, 0x00, // '{'
+    0x00, 0x00, 0x7F, 0x00, 0x00, // '|'
+    0x00, 0x41, 0x36, 0x08, 0x00, // '}'
+    0x02, 0x01, 0x02, 0x04, 0x02, // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_atlas() {
+        let atlas = font_atlas();
+        let width = ATLAS_NCOL * CELL_W;
+        assert_eq!(atlas.len(), width * ATLAS_NROW * CELL_H);
+        let texel = |c: char, ix: usize, iy: usize| {
+            let code = c as usize - 32;
+            let (col, row) = (code % ATLAS_NCOL, code / ATLAS_NCOL);
+            atlas[(row * CELL_H + iy) * width + col * CELL_W + ix]
+        };
+        // the space is empty
+        assert!((0..CELL_W).all(|ix| (0..CELL_H).all(|iy| texel(' ', ix, iy) == 0)));
+        // the vertical bar of '|' is the middle column of the top seven rows
+        for iy in 0..CELL_H {
+            assert_eq!(texel('|', 2, iy), if iy < 7 { 255 } else { 0 });
+            assert_eq!(texel('|', 1, iy), 0);
+        }
+        // the columns beyond the five of a glyph are empty
+        assert!((5..CELL_W).all(|ix| (0..CELL_H).all(|iy| texel('W', ix, iy) == 0)));
+        // every glyph except the space has a texel
+        for c in '!'..='~' {
+            assert!((0..5).any(|ix| (0..CELL_H).any(|iy| texel(c, ix, iy) != 0)));
+        }
+    }
+
+    /// data of the `i_vtx`-th vertex of the labels
+    fn vtx(drawer: &Drawer, i_vtx: usize) -> &[f32] {
+        &drawer.glyph2vtxdata[i_vtx * NUM_FLOAT_PER_VTX..(i_vtx + 1) * NUM_FLOAT_PER_VTX]
+    }
+
+    #[test]
+    fn test_label_layout() {
+        let mut drawer = Drawer::new();
+        drawer.add_label(Anchor::World([1.0, 2.0, 3.0]), "a b", [1.0, 0.0, 0.0]);
+        // the space is skipped but advances the position
+        assert_eq!(drawer.num_glyph(), 2);
+        let (v0, v4) = (vtx(&drawer, 0), vtx(&drawer, 4));
+        assert_eq!(&v0[0..4], &[1.0, 2.0, 3.0, 0.0]);
+        assert_eq!(&v0[8..11], &[1.0, 0.0, 0.0]);
+        assert_eq!(v0[4], 0.0);
+        assert_eq!(v4[4], 2.0 * ADVANCE);
+        // the single line is vertically centered at the anchor
+        let line_height = CELL_H as f32 + 1.0;
+        assert_eq!(vtx(&drawer, 2)[5], 0.5 * line_height);
+        assert_eq!(v0[5], 0.5 * line_height - CELL_H as f32);
+        //
+        drawer.clear();
+        drawer.add_label(Anchor::Pixel([10.0, 20.0]), "x\ny", [0.0; 3]);
+        assert_eq!(drawer.num_glyph(), 2);
+        assert_eq!(&vtx(&drawer, 0)[0..4], &[10.0, 20.0, 0.0, 2.0]);
+        // the second line is one line below the first one, both starting at the anchor
+        assert_eq!(vtx(&drawer, 4)[4], 0.0);
+        assert_eq!(vtx(&drawer, 0)[5] - vtx(&drawer, 4)[5], line_height);
+        // a character outside of the printable ASCII is drawn as '?'
+        let mut drawer0 = Drawer::new();
+        drawer0.add_label(Anchor::Ndc([0.0, 0.0]), "\u{e9}", [0.0; 3]);
+        let mut drawer1 = Drawer::new();
+        drawer1.add_label(Anchor::Ndc([0.0, 0.0]), "?", [0.0; 3]);
+        assert_eq!(drawer0.glyph2vtxdata, drawer1.glyph2vtxdata);
+        assert_eq!(vtx(&drawer0, 0)[3], 1.0);
+    }
+
+    #[test]
+    fn test_batching() {
+        let mut drawer = Drawer::new();
+        for i in 0..1000 {
+            drawer.add_label(
+                Anchor::World([i as f32, 0.0, 0.0]),
+                &i.to_string(),
+                [0.0; 3],
+            );
+        }
+        // 10 + 90 * 2 + 900 * 3 glyphs in one buffer
+        let num_glyph = 10 + 90 * 2 + 900 * 3;
+        assert_eq!(drawer.num_glyph(), num_glyph);
+        let idx = glyph2idx(num_glyph);
+        assert_eq!(idx.len(), num_glyph * 6);
+        assert_eq!(&idx[6..12], &[4, 5, 6, 4, 6, 7]);
+        assert_eq!(*idx.iter().max().unwrap() as usize, num_glyph * 4 - 1);
+    }
+}
@@ -1,9 +1,32 @@
 use glow::HasContext;
 
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform bool isZFlip;
+layout (location = 0) in vec3 in_position;
+layout (location = 1) in vec3 in_color;
+out vec3 color;
+void main() {
+    color = in_color;
+    gl_Position = frame_project(frameMatView * vec4(in_position, 1.0), isZFlip);
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+in vec3 color;
+out vec4 f_color;
+void main() {
+    f_color = vec4(color, 1.0);
+}
+"#;
+
 pub struct Drawer {
     vertex_array: Option<glow::NativeVertexArray>,
-    pub program: Option<glow::Program>,
-    uniform_loc_mvp: Option<glow::NativeUniformLocation>,
+    vbo_xyz: Option<glow::NativeBuffer>,
+    vbo_rgb: Option<glow::NativeBuffer>,
+    pub program: Option<crate::shader_program::ShaderProgram>,
+    /// flip z of the projection (see [`crate::frame_uniforms::FrameUniforms::is_z_flip`])
+    pub is_z_flip: bool,
     num_elem: usize,
     num_node: usize,
 }
@@ -13,72 +36,69 @@ impl Drawer {
         Drawer {
             program: None,
             vertex_array: None,
-            uniform_loc_mvp: None,
+            vbo_xyz: None,
+            vbo_rgb: None,
+            is_z_flip: false,
             num_elem: 3,
             num_node: 3,
         }
     }
 
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-
-        let vs_src = r#"
-        uniform mat4 Mvp;
-        layout (location=0) in vec3 in_position;
-        layout (location=1) in vec3 in_color;
-        out vec3 color;
-        void main() {
-            color = in_color;
-            gl_Position = Mvp * vec4(in_position, 1.0);
-        }
-"#;
-        let fs_src = r#"
-                in vec3 color;
-                out vec4 f_color;
-                void main() {
-                    f_color = vec4(color, 1.0);
-                }
-"#;
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
         unsafe {
-            self.program = crate::compile_shaders(gl, shader_version, vs_src, fs_src);
-            {
-                let vao0 = gl.create_vertex_array().unwrap();
-                self.vertex_array = Some(vao0);
-                //
-                let program = self.program.unwrap();
-                self.uniform_loc_mvp = gl.get_uniform_location(program, "Mvp");
-                //
-                let attrib_loc_position = gl
-                    .get_attrib_location(self.program.unwrap(), "in_position")
-                    .unwrap();
-                let attrib_loc_color = gl
-                    .get_attrib_location(self.program.unwrap(), "in_color")
-                    .unwrap();
-                dbg!(attrib_loc_position, attrib_loc_color);
-                gl.bind_vertex_array(self.vertex_array);
-            }
+            let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+            self.set_program(gl, program);
+            let vao0 = gl.create_vertex_array().unwrap();
+            self.vertex_array = Some(vao0);
         }
     }
 
-    pub fn update_tri2node2xyz(&mut self, gl: &glow::Context, tri2node2xyz: &[f32]) {
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// error if the length is not a multiple of 9
+    pub fn update_tri2node2xyz(
+        &mut self,
+        gl: &glow::Context,
+        tri2node2xyz: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("tri2node2xyz", tri2node2xyz.len(), 9)?;
         self.num_elem = tri2node2xyz.len() / 9;
         self.num_node = 3;
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
-            let vbo = gl.create_buffer().unwrap();
+            let vbo = *self
+                .vbo_xyz
+                .get_or_insert_with(|| gl.create_buffer().unwrap());
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
                 bytemuck::cast_slice(tri2node2xyz),
                 glow::STATIC_DRAW,
             );
-            let attrib_loc_position = gl
-                .get_attrib_location(self.program.unwrap(), "in_position")
-                .unwrap();
+            let Some(attrib_loc_position) = self
+                .program
+                .as_ref()
+                .unwrap()
+                .attrib_location("in_position")
+            else {
+                // optimized out by the driver as it does not contribute to the output
+                gl.bind_vertex_array(None);
+                return Ok(());
+            };
             gl.enable_vertex_attrib_array(attrib_loc_position);
             gl.vertex_attrib_pointer_f32(
                 attrib_loc_position,
@@ -91,23 +111,34 @@ impl Drawer {
             gl.bind_buffer(glow::ARRAY_BUFFER, None);
             gl.bind_vertex_array(None);
         }
+        Ok(())
     }
 
-    pub fn update_tri2node2rgb(&mut self, gl: &glow::Context, tri2color: &[f32]) {
-        // assert_eq!(tri2color.len(), self.num_elem * 3);
+    /// error if the length differs from the one of `tri2node2xyz`
+    pub fn update_tri2node2rgb(
+        &mut self,
+        gl: &glow::Context,
+        tri2color: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::length("tri2node2rgb", tri2color.len(), self.num_elem * 9)?;
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
             //
-            let vbo = gl.create_buffer().unwrap();
+            let vbo = *self
+                .vbo_rgb
+                .get_or_insert_with(|| gl.create_buffer().unwrap());
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
                 bytemuck::cast_slice(tri2color),
                 glow::STATIC_DRAW,
             );
-            let attrib_loc_color = gl
-                .get_attrib_location(self.program.unwrap(), "in_color")
-                .unwrap();
+            let Some(attrib_loc_color) = self.program.as_ref().unwrap().attrib_location("in_color")
+            else {
+                // optimized out by the driver as it does not contribute to the output
+                gl.bind_vertex_array(None);
+                return Ok(());
+            };
             gl.enable_vertex_attrib_array(attrib_loc_color);
             gl.vertex_attrib_pointer_f32(
                 attrib_loc_color,
@@ -120,18 +151,36 @@ impl Drawer {
             gl.bind_buffer(glow::ARRAY_BUFFER, None);
             gl.bind_vertex_array(None);
         }
+        Ok(())
     }
 
-    pub fn draw(&self, gl: &glow::Context, mat_modelview: &[f32; 16], mat_projection: &[f32; 16]) {
-        let mvp = del_geo_core::mat4_col_major::mult_mat_col_major(mat_projection, mat_modelview);
+    /// draw with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
             gl.bind_vertex_array(self.vertex_array);
-            gl.use_program(self.program);
-            gl.uniform_matrix_4_f32_slice(self.uniform_loc_mvp.as_ref(), false, &mvp);
+            gl.use_program(Some(program.program));
+            program.set_bool(gl, "isZFlip", self.is_z_flip).unwrap();
             gl.draw_arrays(glow::TRIANGLES, 0, (self.num_elem * self.num_node) as i32);
             gl.bind_vertex_array(None);
         }
     }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        unsafe {
+            if let Some(program) = &self.program {
+                program.destroy(gl);
+            }
+            if let Some(vao) = self.vertex_array {
+                gl.delete_vertex_array(vao);
+            }
+            for vbo in [self.vbo_xyz, self.vbo_rgb].into_iter().flatten() {
+                gl.delete_buffer(vbo);
+            }
+        }
+    }
 }
 
 impl Default for Drawer {
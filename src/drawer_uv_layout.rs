@@ -0,0 +1,489 @@
+//! draw a triangle mesh laid out in the UV space to inspect the parameterization
+//!
+//! Each triangle is colored by its distortion between the 3D surface and the UV space.
+//! The flipped triangles and the overlapping triangles are highlighted.
+//! This is the UV layout mode of [`crate::drawer_elem2vtx_vtx2xyz_vtx2uv::Drawer`].
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distortion {
+    /// flat color
+    None,
+    /// `log2` of the ratio of the normalized UV area to the normalized 3D area,
+    /// drawn with the coolwarm colormap in [-1,1] (blue if shrunk, red if enlarged)
+    Area,
+    /// `log2` of the ratio of the singular values of the Jacobian (0 for the conformal map),
+    /// drawn with the viridis colormap in [0,1]
+    Angle,
+}
+
+pub struct Drawer {
+    drawer_tri: crate::drawer_tri2node2xyz_tri2node2rgb::Drawer,
+    pub drawer_edge: crate::drawer_elem2vtx_vtx2xyz::Drawer,
+    pub is_edge_visible: bool,
+    pub color: [f32; 3],
+    /// color of the flipped or degenerate triangles and of the ones without the distortion
+    pub color_flipped: [f32; 3],
+    pub color_overlapped: [f32; 3],
+    tri2distortion: Vec<f32>,
+    tri2is_flipped: Vec<bool>,
+    tri2is_overlapped: Vec<bool>,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        // z is flipped as [`crate::drawer_elem2vtx_vtx2xyz_vtx2uv::Drawer::draw`] does for the 3D mesh
+        let mut drawer_tri = crate::drawer_tri2node2xyz_tri2node2rgb::Drawer::new();
+        drawer_tri.is_z_flip = true;
+        let mut drawer_edge = crate::drawer_elem2vtx_vtx2xyz::Drawer::new();
+        drawer_edge.is_z_flip = true;
+        Drawer {
+            drawer_tri,
+            drawer_edge,
+            is_edge_visible: true,
+            color: [0.8, 0.8, 0.8],
+            color_flipped: [1.0, 0.0, 1.0],
+            color_overlapped: [1.0, 1.0, 0.0],
+            tri2distortion: vec![],
+            tri2is_flipped: vec![],
+            tri2is_overlapped: vec![],
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_tri.compile_shader(gl, builder);
+        self.drawer_edge.compile_shader(gl, builder);
+    }
+
+    /// compute the distortion, the flips and the overlaps of the triangles and upload the layout
+    pub fn set_tri2vtx_vtx2xyz_vtx2uv(
+        &mut self,
+        gl: &glow::Context,
+        tri2vtx: &[usize],
+        vtx2xyz: &[f32],
+        vtx2uv: &[f32],
+        distortion: Distortion,
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("tri2vtx", tri2vtx.len(), 3)?;
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::length("vtx2uv", vtx2uv.len(), vtx2xyz.len() / 3 * 2)?;
+        crate::validate::index_range("tri2vtx", tri2vtx, vtx2xyz.len() / 3)?;
+        let num_tri = tri2vtx.len() / 3;
+        let uv = |i_vtx: usize| arrayref::array_ref![vtx2uv, i_vtx * 2, 2];
+        let tri2area_uv = tri2area_uv(tri2vtx, vtx2uv);
+        self.tri2is_flipped = tri2is_flipped(&tri2area_uv);
+        self.tri2is_overlapped = tri2is_overlapped(tri2vtx, vtx2uv);
+        self.tri2distortion = tri2distortion(tri2vtx, vtx2xyz, vtx2uv, &tri2area_uv, distortion);
+        //
+        let mut tri2node2xyz = Vec::<f32>::with_capacity(num_tri * 9);
+        let mut tri2node2rgb = Vec::<f32>::with_capacity(num_tri * 9);
+        for i_tri in 0..num_tri {
+            for i_node in 0..3 {
+                let p = uv(tri2vtx[i_tri * 3 + i_node]);
+                tri2node2xyz.extend_from_slice(&[p[0], p[1], 0.0]);
+            }
+            let rgb = self.tri_color(i_tri, distortion);
+            tri2node2rgb.extend_from_slice(&[rgb; 3].concat());
+        }
+        self.drawer_tri.update_tri2node2xyz(gl, &tri2node2xyz)?;
+        self.drawer_tri.update_tri2node2rgb(gl, &tri2node2rgb)?;
+        //
+        let edge2vtx: Vec<usize> = (0..num_tri)
+            .flat_map(|i_tri| {
+                let t = &tri2vtx[i_tri * 3..i_tri * 3 + 3];
+                [t[0], t[1], t[1], t[2], t[2], t[0]]
+            })
+            .collect();
+        self.drawer_edge.clear_elem2vtx(gl);
+        self.drawer_edge
+            .add_elem2vtx(gl, glow::LINES, &edge2vtx, [0., 0., 0.])?;
+        self.drawer_edge.set_vtx2xyz(gl, vtx2uv, 2)?;
+        Ok(())
+    }
+
+    /// color of the triangle by its classification and its distortion
+    fn tri_color(&self, i_tri: usize, distortion: Distortion) -> [f32; 3] {
+        let d = self.tri2distortion[i_tri];
+        if self.tri2is_flipped[i_tri] || d.is_nan() {
+            return self.color_flipped;
+        }
+        if self.tri2is_overlapped[i_tri] {
+            return self.color_overlapped;
+        }
+        match distortion {
+            Distortion::None => self.color,
+            Distortion::Area => crate::colormap::coolwarm(0.5 + 0.5 * d),
+            Distortion::Angle => crate::colormap::viridis(d),
+        }
+    }
+
+    /// distortion of each triangle (see [`Distortion`]). Zero for [`Distortion::None`].
+    /// NaN where it is undefined (the degenerate 3D triangles or the layout collapsed to zero area)
+    pub fn tri2distortion(&self) -> &[f32] {
+        &self.tri2distortion
+    }
+
+    /// the triangles whose orientation in the UV space is opposite to the majority
+    pub fn tri2is_flipped(&self) -> &[bool] {
+        &self.tri2is_flipped
+    }
+
+    /// the triangles whose interior overlaps the one of another triangle in the UV space
+    pub fn tri2is_overlapped(&self) -> &[bool] {
+        &self.tri2is_overlapped
+    }
+
+    /// the z of the projection is flipped as [`crate::drawer_elem2vtx_vtx2xyz_vtx2uv::Drawer::draw`]
+    /// does for the 3D mesh, for both the fill and the edges
+    pub fn draw(&self, gl: &glow::Context) {
+        use glow::HasContext;
+        unsafe {
+            // push the fill behind the edges
+            gl.enable(glow::POLYGON_OFFSET_FILL);
+            gl.polygon_offset(1.0, 1.0);
+            self.drawer_tri.draw(gl);
+            gl.disable(glow::POLYGON_OFFSET_FILL);
+        }
+        if self.is_edge_visible {
+            self.drawer_edge.draw(gl);
+        }
+    }
+
+    pub fn destroy(&self, gl: &glow::Context) {
+        self.drawer_tri.destroy(gl);
+        self.drawer_edge.destroy(gl);
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// singular values (descending) of the column-major 2x2 matrix
+fn singular_values2(m: &[f32; 4]) -> (f32, f32) {
+    let (a, c, b, d) = (m[0], m[1], m[2], m[3]);
+    let e = 0.5 * (a + d);
+    let f = 0.5 * (a - d);
+    let g = 0.5 * (c + b);
+    let h = 0.5 * (c - b);
+    let q = (e * e + h * h).sqrt();
+    let r = (f * f + g * g).sqrt();
+    (q + r, (q - r).abs())
+}
+
+/// distortion of each triangle (see [`Distortion`]). NaN for the degenerate 3D triangles and
+/// for the layout collapsed to zero area, where the map to the UV space is not defined
+fn tri2distortion(
+    tri2vtx: &[usize],
+    vtx2xyz: &[f32],
+    vtx2uv: &[f32],
+    tri2area_uv: &[f32],
+    distortion: Distortion,
+) -> Vec<f32> {
+    let num_tri = tri2vtx.len() / 3;
+    if distortion == Distortion::None {
+        return vec![0.0; num_tri];
+    }
+    let xyz = |i_vtx: usize| arrayref::array_ref![vtx2xyz, i_vtx * 3, 3];
+    let uv = |i_vtx: usize| arrayref::array_ref![vtx2uv, i_vtx * 2, 2];
+    // the Jacobian of the map from the 3D triangle to the UV triangle
+    let tri2jacobian: Vec<([f32; 4], f32)> = (0..num_tri)
+        .map(|i_tri| {
+            let [i0, i1, i2] = std::array::from_fn(|i| tri2vtx[i_tri * 3 + i]);
+            let e1 = del_geo_core::vec3::sub(xyz(i1), xyz(i0));
+            let e2 = del_geo_core::vec3::sub(xyz(i2), xyz(i0));
+            let area3 = 0.5 * del_geo_core::vec3::norm(&del_geo_core::vec3::cross(&e1, &e2));
+            // e1 and e2 in the orthonormal frame on the triangle
+            let len1 = del_geo_core::vec3::norm(&e1);
+            let q = [
+                len1,
+                0.,
+                del_geo_core::vec3::dot(&e2, &e1) / len1,
+                2. * area3 / len1,
+            ];
+            let (uv0, uv1, uv2) = (uv(i0), uv(i1), uv(i2));
+            let u = [
+                uv1[0] - uv0[0],
+                uv1[1] - uv0[1],
+                uv2[0] - uv0[0],
+                uv2[1] - uv0[1],
+            ];
+            // J = U Q^-1 (column major 2x2)
+            let det_q = q[0] * q[3] - q[2] * q[1];
+            let q_inv = [q[3] / det_q, -q[1] / det_q, -q[2] / det_q, q[0] / det_q];
+            let j = [
+                u[0] * q_inv[0] + u[2] * q_inv[1],
+                u[1] * q_inv[0] + u[3] * q_inv[1],
+                u[0] * q_inv[2] + u[2] * q_inv[3],
+                u[1] * q_inv[2] + u[3] * q_inv[3],
+            ];
+            (j, area3)
+        })
+        .collect();
+    let total_uv: f32 = tri2area_uv.iter().map(|a| a.abs()).sum();
+    let total_3d: f32 = tri2jacobian.iter().map(|(_, a)| a).sum();
+    (0..num_tri)
+        .map(|i_tri| {
+            let (j, area3) = &tri2jacobian[i_tri];
+            if !(*area3 > 0.0 && total_uv > 0.0) {
+                return f32::NAN;
+            }
+            let d = match distortion {
+                Distortion::None => 0.0,
+                Distortion::Area => {
+                    ((tri2area_uv[i_tri].abs() / total_uv) / (area3 / total_3d)).log2()
+                }
+                Distortion::Angle => {
+                    let (s0, s1) = singular_values2(j);
+                    (s0 / s1).log2()
+                }
+            };
+            // e.g., the degenerate UV triangle
+            if d.is_finite() {
+                d
+            } else {
+                f32::NAN
+            }
+        })
+        .collect()
+}
+
+/// signed area of each triangle in the UV space
+fn tri2area_uv(tri2vtx: &[usize], vtx2uv: &[f32]) -> Vec<f32> {
+    let uv = |i_vtx: usize| arrayref::array_ref![vtx2uv, i_vtx * 2, 2];
+    tri2vtx
+        .chunks(3)
+        .map(|t| del_geo_core::tri2::area(uv(t[0]), uv(t[1]), uv(t[2])))
+        .collect()
+}
+
+/// the triangles whose orientation is opposite to the majority (weighted by the area),
+/// or degenerate
+fn tri2is_flipped(tri2area_uv: &[f32]) -> Vec<bool> {
+    let sign = tri2area_uv.iter().sum::<f32>().signum();
+    tri2area_uv.iter().map(|a| a * sign <= 0.0).collect()
+}
+
+/// overlap of the interiors of the triangles in the UV space, including the triangles sharing
+/// a vertex (e.g., a fan wrapping more than 360 degrees). The candidate pairs are found with a
+/// uniform grid of the bounding boxes
+fn tri2is_overlapped(tri2vtx: &[usize], vtx2uv: &[f32]) -> Vec<bool> {
+    let num_tri = tri2vtx.len() / 3;
+    let mut tri2is_overlapped = vec![false; num_tri];
+    if num_tri == 0 {
+        return tri2is_overlapped;
+    }
+    let tri2pos = |i_tri: usize| -> [[f32; 2]; 3] {
+        std::array::from_fn(|i| *arrayref::array_ref![vtx2uv, tri2vtx[i_tri * 3 + i] * 2, 2])
+    };
+    let tri2aabb: Vec<[f32; 4]> = (0..num_tri)
+        .map(|i_tri| {
+            tri2pos(i_tri)
+                .iter()
+                .fold([f32::MAX, f32::MAX, f32::MIN, f32::MIN], |b, p| {
+                    [
+                        b[0].min(p[0]),
+                        b[1].min(p[1]),
+                        b[2].max(p[0]),
+                        b[3].max(p[1]),
+                    ]
+                })
+        })
+        .collect();
+    let aabb = tri2aabb
+        .iter()
+        .fold([f32::MAX, f32::MAX, f32::MIN, f32::MIN], |b, a| {
+            [
+                b[0].min(a[0]),
+                b[1].min(a[1]),
+                b[2].max(a[2]),
+                b[3].max(a[3]),
+            ]
+        });
+    let n = ((num_tri as f32).sqrt().ceil() as usize).max(1);
+    let cell = |x: f32, i: usize| {
+        let (min, max) = (aabb[i], aabb[i + 2]);
+        (((x - min) / (max - min).max(f32::EPSILON) * n as f32) as usize).min(n - 1)
+    };
+    let mut cell2tris = vec![Vec::<usize>::new(); n * n];
+    for (i_tri, b) in tri2aabb.iter().enumerate() {
+        for iy in cell(b[1], 1)..=cell(b[3], 1) {
+            for ix in cell(b[0], 0)..=cell(b[2], 0) {
+                cell2tris[iy * n + ix].push(i_tri);
+            }
+        }
+    }
+    let mut tested = std::collections::HashSet::<(usize, usize)>::new();
+    for tris in &cell2tris {
+        for (k, &i_tri) in tris.iter().enumerate() {
+            for &j_tri in &tris[k + 1..] {
+                let (bi, bj) = (&tri2aabb[i_tri], &tri2aabb[j_tri]);
+                if bi[2] < bj[0] || bj[2] < bi[0] || bi[3] < bj[1] || bj[3] < bi[1] {
+                    continue;
+                }
+                if !tested.insert((i_tri, j_tri)) {
+                    continue;
+                }
+                if is_intersect_tri2(&tri2pos(i_tri), &tri2pos(j_tri)) {
+                    tri2is_overlapped[i_tri] = true;
+                    tri2is_overlapped[j_tri] = true;
+                }
+            }
+        }
+    }
+    tri2is_overlapped
+}
+
+/// the interiors of the two triangles intersect, tested with the separating axes of the edges.
+/// The triangles touching at an edge or a vertex (within a relative tolerance) do not intersect,
+/// and the degenerate triangle has no interior
+fn is_intersect_tri2(a: &[[f32; 2]; 3], b: &[[f32; 2]; 3]) -> bool {
+    let area_a = del_geo_core::tri2::area(&a[0], &a[1], &a[2]);
+    let area_b = del_geo_core::tri2::area(&b[0], &b[1], &b[2]);
+    for (t, area) in [(a, area_a), (b, area_b)] {
+        let len2 = (0..3)
+            .map(|i| {
+                del_geo_core::vec2::squared_length(&del_geo_core::vec2::sub(&t[(i + 1) % 3], &t[i]))
+            })
+            .fold(0f32, f32::max);
+        if area.abs() <= 1.0e-6 * len2 {
+            return false;
+        }
+    }
+    for t in [a, b] {
+        for i in 0..3 {
+            let (p0, p1) = (&t[i], &t[(i + 1) % 3]);
+            let axis = [p0[1] - p1[1], p1[0] - p0[0]];
+            let range = |t: &[[f32; 2]; 3]| {
+                t.iter()
+                    .map(|p| axis[0] * p[0] + axis[1] * p[1])
+                    .fold([f32::MAX, f32::MIN], |r, v| [r[0].min(v), r[1].max(v)])
+            };
+            let (ra, rb) = (range(a), range(b));
+            let eps = 1.0e-5 * (ra[1].max(rb[1]) - ra[0].min(rb[0]));
+            if ra[1] <= rb[0] + eps || rb[1] <= ra[0] + eps {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_singular_values2() {
+        // rotation by 30 degrees scaled by (3, 2) along the rotated axes
+        let (c, s) = (30f32.to_radians().cos(), 30f32.to_radians().sin());
+        let m = [3. * c, 3. * s, -2. * s, 2. * c];
+        let (s0, s1) = singular_values2(&m);
+        assert!((s0 - 3.0).abs() < 1.0e-5 && (s1 - 2.0).abs() < 1.0e-5);
+        // reflection keeps the singular values
+        let (s0, s1) = singular_values2(&[0., 2., 5., 0.]);
+        assert!((s0 - 5.0).abs() < 1.0e-5 && (s1 - 2.0).abs() < 1.0e-5);
+        // rank 1
+        assert!(singular_values2(&[1., 2., 2., 4.]).1.abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_tri2distortion() {
+        // two triangles of a square mapped to the unit square; the second 3D triangle has
+        // zero area
+        let vtx2xyz = [0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0., 2., 0., 0.];
+        let vtx2uv = [0., 0., 1., 0., 1., 1., 0., 1., 1., 0.5];
+        let tri2vtx = [0, 1, 2, 0, 2, 3, 0, 1, 4];
+        let tri2area_uv = tri2area_uv(&tri2vtx, &vtx2uv);
+        for distortion in [Distortion::Area, Distortion::Angle] {
+            let d = tri2distortion(&tri2vtx, &vtx2xyz, &vtx2uv, &tri2area_uv, distortion);
+            assert!(
+                d[0].is_finite() && (d[0] - d[1]).abs() < 1.0e-5,
+                "{distortion:?}"
+            );
+            assert!(d[2].is_nan(), "{distortion:?}");
+        }
+        let d = tri2distortion(&tri2vtx, &vtx2xyz, &vtx2uv, &tri2area_uv, Distortion::Angle);
+        assert!(d[0].abs() < 1.0e-5);
+        // the layout collapsed to a point
+        let vtx2uv = [0.5; 10];
+        let tri2area_uv = super::tri2area_uv(&tri2vtx, &vtx2uv);
+        for distortion in [Distortion::Area, Distortion::Angle] {
+            let d = tri2distortion(&tri2vtx, &vtx2xyz, &vtx2uv, &tri2area_uv, distortion);
+            assert!(d.iter().all(|v| v.is_nan()), "{distortion:?}");
+        }
+        let d = tri2distortion(&tri2vtx, &vtx2xyz, &vtx2uv, &tri2area_uv, Distortion::None);
+        assert_eq!(d, vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_tri_color() {
+        let mut drawer = Drawer::new();
+        drawer.tri2distortion = vec![0.0, f32::NAN, 0.0];
+        drawer.tri2is_flipped = vec![false, false, false];
+        drawer.tri2is_overlapped = vec![false, false, true];
+        let c = drawer.tri_color(0, Distortion::Area);
+        assert!(c.iter().all(|v| v.is_finite()));
+        assert_ne!(c, drawer.color_flipped);
+        assert_eq!(drawer.tri_color(1, Distortion::Area), drawer.color_flipped);
+        assert_eq!(drawer.tri_color(1, Distortion::Angle), drawer.color_flipped);
+        assert_eq!(
+            drawer.tri_color(2, Distortion::Area),
+            drawer.color_overlapped
+        );
+    }
+
+    #[test]
+    fn test_is_intersect_tri2() {
+        let a = [[0., 0.], [1., 0.], [0., 1.]];
+        // coincident
+        assert!(is_intersect_tri2(&a, &a));
+        assert!(is_intersect_tri2(&a, &[[1., 0.], [0., 1.], [0., 0.]]));
+        // sharing an edge
+        assert!(!is_intersect_tri2(&a, &[[1., 0.], [1., 1.], [0., 1.]]));
+        // sharing a vertex
+        assert!(!is_intersect_tri2(&a, &[[0., 0.], [-1., 0.], [0., -1.]]));
+        // contained
+        assert!(is_intersect_tri2(&a, &[[0.1, 0.1], [0.3, 0.1], [0.1, 0.3]]));
+        // crossing
+        assert!(is_intersect_tri2(
+            &a,
+            &[[0.5, -0.5], [0.5, 2.], [-0.5, 0.2]]
+        ));
+        // degenerate
+        assert!(!is_intersect_tri2(&a, &[[0., 0.], [1., 1.], [0.5, 0.5]]));
+    }
+
+    #[test]
+    fn test_classification() {
+        // fan of 5 triangles around the vertex 0 turning 90 degrees each, so the last one
+        // wraps onto the first one
+        let mut vtx2uv = vec![0f32, 0.];
+        for i in 0..6 {
+            let theta = (i as f32 * 90.0).to_radians();
+            vtx2uv.extend_from_slice(&[theta.cos(), theta.sin()]);
+        }
+        let tri2vtx: Vec<usize> = (0..5).flat_map(|i| [0, i + 1, i + 2]).collect();
+        let is_overlapped = tri2is_overlapped(&tri2vtx, &vtx2uv);
+        assert_eq!(is_overlapped, vec![true, false, false, false, true]);
+        assert_eq!(
+            tri2is_flipped(&tri2area_uv(&tri2vtx, &vtx2uv)),
+            vec![false; 5]
+        );
+        // one of the two triangles of a square is flipped
+        let vtx2uv = [0., 0., 1., 0., 1., 1., 0., 1., 0.5, 0.5];
+        let tri2vtx = [0, 1, 2, 0, 2, 3, 0, 1, 4, 4, 3, 2];
+        let tri2area_uv = tri2area_uv(&tri2vtx, &vtx2uv);
+        assert_eq!(
+            tri2is_flipped(&tri2area_uv),
+            vec![false, false, false, true]
+        );
+        let tri2is_overlapped = tri2is_overlapped(&tri2vtx, &vtx2uv);
+        assert_eq!(tri2is_overlapped, vec![true, true, true, true]);
+    }
+}
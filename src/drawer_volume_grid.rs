@@ -0,0 +1,442 @@
+//! direct volume rendering of a scalar field on a 3D grid
+//!
+//! The grid is uploaded as a 3D texture and ray-marched in the fragment shader. The opaque
+//! meshes are drawn into an offscreen buffer between [`Drawer::begin`] and [`Drawer::end`],
+//! so that the rays stop at the meshes and the volume is composited over them.
+//!
+//! ```ignore
+//! drawer_volume.begin(gl, &[1.0, 1.0, 1.0, 1.0]);
+//! drawer_mesh.draw(gl);
+//! drawer_volume.end(gl);
+//! ```
+
+use glow::HasContext;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    /// front-to-back alpha compositing with the transfer function
+    Composite,
+    /// maximum intensity projection
+    MaximumIntensity,
+    /// shaded surface at `iso_value`
+    Isosurface,
+}
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "vertex_id"
+out vec2 uv;
+// the points on the near plane and the far plane in the homogeneous coordinate
+out vec4 nearPoint;
+out vec4 farPoint;
+void main() {
+    uv = fullscreen_triangle_uv();
+    vec2 p = uv * 2.0 - 1.0;
+    mat4 mvp_inv = inverse(frameMatPrj * frameMatView);
+    nearPoint = mvp_inv * vec4(p, -1.0, 1.0);
+    farPoint = mvp_inv * vec4(p, 1.0, 1.0);
+    gl_Position = vec4(p, 0.0, 1.0);
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+#include "frame_uniforms"
+#include "depth"
+uniform sampler2D colorTex;
+uniform sampler2D depthTex;
+uniform sampler3D volumeTex;
+uniform sampler2D transferTex;
+uniform vec3 boxMin;
+uniform vec3 boxMax;
+uniform int mode;
+uniform int numStep;
+uniform float opacityScale;
+uniform float isoValue;
+const float NUM_STEP_REFERENCE = 256.0;
+
+in vec2 uv;
+in vec4 nearPoint;
+in vec4 farPoint;
+out vec4 FragColor;
+
+float value(vec3 p) {
+    return texture(volumeTex, (p - boxMin) / (boxMax - boxMin)).r;
+}
+
+void main() {
+    vec4 scene = texture(colorTex, uv);
+    float depth_scene = texture(depthTex, uv).r;
+    gl_FragDepth = depth_scene;
+    FragColor = scene;
+    // ray parameterized as p0 + t * (p1 - p0) from the near plane to the far plane
+    vec3 p0 = nearPoint.xyz / nearPoint.w;
+    vec3 p1 = farPoint.xyz / farPoint.w;
+    vec3 d = p1 - p0;
+    vec3 inv_d = 1.0 / d;
+    vec3 ta = (boxMin - p0) * inv_d;
+    vec3 tb = (boxMax - p0) * inv_d;
+    vec3 tmin = min(ta, tb);
+    vec3 tmax = max(ta, tb);
+    float t_enter = max(max(max(tmin.x, tmin.y), tmin.z), 0.0);
+    float t_exit = min(min(tmax.x, tmax.y), tmax.z);
+    // the ray stops at the opaque meshes
+    // the homogeneous point is linear in the window depth
+    vec4 h_scene = mix(nearPoint, farPoint, depth_scene);
+    vec3 p_scene = h_scene.xyz / h_scene.w;
+    t_exit = min(t_exit, dot(p_scene - p0, d) / dot(d, d));
+    if( !(t_enter < t_exit) ){ return; } // also for the singular camera
+    float dt = length(boxMax - boxMin) / (float(numStep) * length(d));
+    float step_ref = length(boxMax - boxMin) / NUM_STEP_REFERENCE;
+    if( mode == 0 ){ // composite
+        vec4 acc = vec4(0.0);
+        for(float t = t_enter; t < t_exit && acc.a < 0.99; t += dt){
+            vec4 c = texture(transferTex, vec2(value(p0 + t * d), 0.5));
+            // the opacity is the one per the reference step, corrected for the actual step
+            float a = 1.0 - pow(1.0 - clamp(c.a * opacityScale, 0.0, 1.0), dt * length(d) / step_ref);
+            acc.rgb += (1.0 - acc.a) * a * c.rgb;
+            acc.a += (1.0 - acc.a) * a;
+        }
+        FragColor = vec4(acc.rgb + (1.0 - acc.a) * scene.rgb, 1.0);
+    }
+    else if( mode == 1 ){ // maximum intensity projection
+        float v_max = 0.0;
+        for(float t = t_enter; t < t_exit; t += dt){
+            v_max = max(v_max, value(p0 + t * d));
+        }
+        vec4 c = texture(transferTex, vec2(v_max, 0.5));
+        float a = clamp(c.a * opacityScale, 0.0, 1.0);
+        FragColor = vec4(mix(scene.rgb, c.rgb, a), 1.0);
+    }
+    else { // isosurface
+        float v_prev = value(p0 + t_enter * d);
+        for(float t = t_enter + dt; t < t_exit; t += dt){
+            float v = value(p0 + t * d);
+            if( (v - isoValue) * (v_prev - isoValue) <= 0.0 && v != v_prev ){
+                float t_hit = t - dt * (v - isoValue) / (v - v_prev);
+                vec3 p = p0 + t_hit * d;
+                vec3 h = (boxMax - boxMin) / vec3(textureSize(volumeTex, 0));
+                vec3 grad = vec3(
+                    value(p + vec3(h.x, 0, 0)) - value(p - vec3(h.x, 0, 0)),
+                    value(p + vec3(0, h.y, 0)) - value(p - vec3(0, h.y, 0)),
+                    value(p + vec3(0, 0, h.z)) - value(p - vec3(0, 0, h.z)));
+                vec3 n = normalize(grad);
+                float shade = 0.3 + 0.7 * abs(dot(n, normalize(d)));
+                vec3 c = texture(transferTex, vec2(isoValue, 0.5)).rgb;
+                FragColor = vec4(c * shade, 1.0);
+                gl_FragDepth = window_depth(frameMatPrj * frameMatView, p);
+                return;
+            }
+            v_prev = v;
+        }
+    }
+}
+"#;
+
+pub struct Drawer {
+    pub mode: RenderMode,
+    /// the grid occupies this box `[min_x, min_y, min_z, max_x, max_y, max_z]`
+    pub aabb: [f32; 6],
+    /// number of the samples along the diagonal of the box
+    pub num_step: usize,
+    /// multiplied to the opacity of the transfer function. The opacity is the one per 1/256 of
+    /// the diagonal of the box, so the appearance does not depend on `num_step`
+    pub opacity_scale: f32,
+    /// value of the isosurface in the unit of the grid
+    pub iso_value: f32,
+    /// values of the grid mapped to [0,1] of the transfer function
+    value_range: [f32; 2],
+    program: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    tex_volume: Option<glow::NativeTexture>,
+    tex_transfer: Option<glow::NativeTexture>,
+    /// color and depth of the meshes
+    target_scene: crate::render_target::RenderTarget,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            mode: RenderMode::Composite,
+            aabb: [-1.0, -1.0, -1.0, 1.0, 1.0, 1.0],
+            num_step: 256,
+            opacity_scale: 1.0,
+            iso_value: 0.5,
+            value_range: [0.0, 1.0],
+            program: None,
+            vertex_array: None,
+            tex_volume: None,
+            tex_transfer: None,
+            target_scene: crate::render_target::RenderTarget::new(
+                &[crate::render_target::FORMAT_RGBA8],
+                true,
+            ),
+        }
+    }
+
+    /// compile the shader and set the default transfer function (jet with a linear opacity ramp)
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        self.set_program(gl, program);
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+        }
+        let tf: Vec<[f32; 4]> = (0..256)
+            .map(|i| {
+                let t = i as f32 / 255.0;
+                let c = crate::colormap::jet(t);
+                [c[0], c[1], c[2], t]
+            })
+            .collect();
+        self.set_transfer_function(gl, &tf);
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// RGBA sampled uniformly over [0,1] of the normalized value
+    pub fn set_transfer_function(&mut self, gl: &glow::Context, tf: &[[f32; 4]]) {
+        let data: Vec<u8> = tf
+            .iter()
+            .flat_map(|c| c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect();
+        unsafe {
+            let tex = *self
+                .tex_transfer
+                .get_or_insert_with(|| gl.create_texture().unwrap());
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                tf.len() as i32,
+                1,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(Some(&data)),
+            );
+            set_sampling(gl, glow::TEXTURE_2D, glow::LINEAR);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+    }
+
+    /// upload the grid of `f32` where `data[(k * dims[1] + j) * dims[0] + i]` is the value at
+    /// `(i, j, k)`. `range` is mapped to [0,1] (the min and max of the data if it is `None`)
+    pub fn set_grid_f32(
+        &mut self,
+        gl: &glow::Context,
+        dims: [usize; 3],
+        data: &[f32],
+        range: Option<[f32; 2]>,
+    ) -> Result<(), String> {
+        crate::validate::length("grid", data.len(), dims[0] * dims[1] * dims[2])?;
+        let range = range.unwrap_or_else(|| {
+            data.iter()
+                .fold([f32::MAX, f32::MIN], |r, &v| [r[0].min(v), r[1].max(v)])
+        });
+        self.value_range = range;
+        let scale = if range[1] > range[0] {
+            1.0 / (range[1] - range[0])
+        } else {
+            1.0
+        };
+        let normalized: Vec<f32> = data.iter().map(|v| (v - range[0]) * scale).collect();
+        // sampled with NEAREST on the target without the linear filtering of R32F
+        let filter = if crate::render_target::is_f32_linear_filterable(gl) {
+            glow::LINEAR
+        } else {
+            glow::NEAREST
+        };
+        self.upload_grid(
+            gl,
+            dims,
+            (glow::R32F, glow::FLOAT),
+            filter,
+            bytemuck::cast_slice(&normalized),
+        );
+        Ok(())
+    }
+
+    /// upload the grid of `u8`. The values 0 to 255 are mapped to [0,1]
+    pub fn set_grid_u8(
+        &mut self,
+        gl: &glow::Context,
+        dims: [usize; 3],
+        data: &[u8],
+    ) -> Result<(), String> {
+        crate::validate::length("grid", data.len(), dims[0] * dims[1] * dims[2])?;
+        self.value_range = [0.0, 255.0];
+        self.upload_grid(
+            gl,
+            dims,
+            (glow::R8, glow::UNSIGNED_BYTE),
+            glow::LINEAR,
+            data,
+        );
+        Ok(())
+    }
+
+    fn upload_grid(
+        &mut self,
+        gl: &glow::Context,
+        dims: [usize; 3],
+        (internal_format, ty): (u32, u32),
+        filter: u32,
+        data: &[u8],
+    ) {
+        unsafe {
+            let tex = *self
+                .tex_volume
+                .get_or_insert_with(|| gl.create_texture().unwrap());
+            gl.bind_texture(glow::TEXTURE_3D, Some(tex));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_3d(
+                glow::TEXTURE_3D,
+                0,
+                internal_format as i32,
+                dims[0] as i32,
+                dims[1] as i32,
+                dims[2] as i32,
+                0,
+                glow::RED,
+                ty,
+                glow::PixelUnpackData::Slice(Some(data)),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            set_sampling(gl, glow::TEXTURE_3D, filter);
+            gl.bind_texture(glow::TEXTURE_3D, None);
+        }
+    }
+
+    /// bind the offscreen buffer of the size of the current viewport and clear it.
+    /// Draw the opaque meshes after this
+    pub fn begin(&mut self, gl: &glow::Context, clear_color: &[f32; 4]) {
+        self.target_scene.begin(gl);
+        unsafe {
+            gl.depth_mask(true);
+            gl.clear_buffer_f32_slice(glow::COLOR, 0, clear_color);
+            gl.clear_buffer_f32_slice(glow::DEPTH, 0, &[1.0]);
+        }
+    }
+
+    /// ray-march the volume over the meshes and composite the result onto the framebuffer
+    /// bound before [`Drawer::begin`] with the camera of [`crate::frame_uniforms`].
+    /// Only the meshes are composited if the camera is singular
+    pub fn end(&self, gl: &glow::Context) {
+        self.target_scene.end(gl);
+        let Some(program) = &self.program else {
+            return;
+        };
+        let aabb = self.aabb;
+        let iso_value = (self.iso_value - self.value_range[0])
+            / (self.value_range[1] - self.value_range[0]).max(f32::EPSILON);
+        unsafe {
+            // state restored at the end
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let depth_func = gl.get_parameter_i32(glow::DEPTH_FUNC) as u32;
+            let prev_active_texture = gl.get_parameter_i32(glow::ACTIVE_TEXTURE) as u32;
+            gl.disable(glow::BLEND);
+            gl.use_program(Some(program.program));
+            gl.bind_vertex_array(self.vertex_array);
+            let textures = [
+                (glow::TEXTURE_2D, self.target_scene.color(0), "colorTex"),
+                (glow::TEXTURE_2D, self.target_scene.depth(), "depthTex"),
+                (glow::TEXTURE_3D, self.tex_volume, "volumeTex"),
+                (glow::TEXTURE_2D, self.tex_transfer, "transferTex"),
+            ];
+            let mut prev_textures = Vec::with_capacity(textures.len());
+            for (unit, (target, tex, name)) in textures.into_iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                let binding = match target {
+                    glow::TEXTURE_3D => glow::TEXTURE_BINDING_3D,
+                    _ => glow::TEXTURE_BINDING_2D,
+                };
+                prev_textures.push((target, gl.get_parameter_texture(binding)));
+                gl.bind_texture(target, tex);
+                program.set_i32(gl, name, unit as i32).unwrap();
+            }
+            program.set_vec3(gl, "boxMin", &aabb[0..3]).unwrap();
+            program.set_vec3(gl, "boxMax", &aabb[3..6]).unwrap();
+            let mode = match self.mode {
+                RenderMode::Composite => 0,
+                RenderMode::MaximumIntensity => 1,
+                RenderMode::Isosurface => 2,
+            };
+            program.set_i32(gl, "mode", mode).unwrap();
+            let num_step = self.num_step.max(1) as i32;
+            program.set_i32(gl, "numStep", num_step).unwrap();
+            program
+                .set_f32(gl, "opacityScale", &[self.opacity_scale])
+                .unwrap();
+            program.set_f32(gl, "isoValue", &[iso_value]).unwrap();
+            // write the depth as well so that the later drawing is occluded
+            gl.enable(glow::DEPTH_TEST);
+            gl.depth_func(glow::ALWAYS);
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.depth_func(depth_func);
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            if is_blend {
+                gl.enable(glow::BLEND);
+            }
+            for (unit, (target, tex)) in prev_textures.into_iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(target, tex);
+            }
+            gl.active_texture(prev_active_texture);
+            gl.bind_vertex_array(None);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.target_scene.destroy(gl);
+        if let Some(program) = self.program.take() {
+            program.destroy(gl);
+        }
+        unsafe {
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            for tex in [self.tex_volume.take(), self.tex_transfer.take()]
+                .into_iter()
+                .flatten()
+            {
+                gl.delete_texture(tex);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// filter and clamp-to-edge wrapping of the bound texture
+unsafe fn set_sampling(gl: &glow::Context, target: u32, filter: u32) {
+    for (param, value) in [
+        (glow::TEXTURE_MIN_FILTER, filter),
+        (glow::TEXTURE_MAG_FILTER, filter),
+        (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+        (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        (glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE),
+    ] {
+        gl.tex_parameter_i32(target, param, value as i32);
+    }
+}
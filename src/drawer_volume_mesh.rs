@@ -0,0 +1,405 @@
+//! draw tetrahedral and hexahedral meshes
+//!
+//! * without a cutting plane, the boundary surface is drawn (or all the element faces if
+//!   the elements are shrunk)
+//! * with a cutting plane, the elements whose centroid is in front of the plane are removed
+//!   and the cross-section of the elements crossing the plane is drawn. The parts of the kept
+//!   elements beyond the plane are discarded in the fragment shader
+//!
+//! The faces are colored by the per-element scalar if it is given.
+//! Call [`Drawer::update`] after changing `shrink` or `plane`.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElemType {
+    /// 4 nodes
+    Tet,
+    /// 8 nodes. The nodes 0-3 are the bottom face and the nodes 4-7 are the top face
+    Hex,
+}
+
+impl ElemType {
+    pub fn num_node(&self) -> usize {
+        match self {
+            ElemType::Tet => 4,
+            ElemType::Hex => 8,
+        }
+    }
+
+    /// local node indices of the faces oriented outward
+    fn faces(&self) -> &'static [&'static [usize]] {
+        match self {
+            ElemType::Tet => &[&[1, 2, 3], &[0, 3, 2], &[0, 1, 3], &[0, 2, 1]],
+            ElemType::Hex => &[
+                &[0, 3, 2, 1],
+                &[4, 5, 6, 7],
+                &[0, 1, 5, 4],
+                &[1, 2, 6, 5],
+                &[2, 3, 7, 6],
+                &[3, 0, 4, 7],
+            ],
+        }
+    }
+
+    fn edges(&self) -> &'static [[usize; 2]] {
+        match self {
+            ElemType::Tet => &[[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]],
+            ElemType::Hex => &[
+                [0, 1],
+                [1, 2],
+                [2, 3],
+                [3, 0],
+                [4, 5],
+                [5, 6],
+                [6, 7],
+                [7, 4],
+                [0, 4],
+                [1, 5],
+                [2, 6],
+                [3, 7],
+            ],
+        }
+    }
+}
+
+pub struct Drawer {
+    pub drawer_polygon: crate::drawer_polygon_mesh::Drawer,
+    /// scale of each element toward its centroid. 1.0 draws the elements as they are
+    pub shrink: f32,
+    /// cutting plane given by a point and the normal. The side of the normal is removed
+    pub plane: Option<([f32; 3], [f32; 3])>,
+    pub color: [f32; 3],
+    elem_type: ElemType,
+    elem2vtx: Vec<usize>,
+    vtx2xyz: Vec<f32>,
+    /// scalar of each element and the range mapped to the colormap
+    elem2scalar: Option<(Vec<f32>, [f32; 2])>,
+    /// element index of each drawn face
+    face2elem: Vec<usize>,
+}
+
+impl Drawer {
+    pub fn new() -> Self {
+        Drawer {
+            drawer_polygon: crate::drawer_polygon_mesh::Drawer::new(),
+            shrink: 1.0,
+            plane: None,
+            color: [0.8, 0.8, 0.8],
+            elem_type: ElemType::Tet,
+            elem2vtx: vec![],
+            vtx2xyz: vec![],
+            elem2scalar: None,
+            face2elem: vec![],
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        self.drawer_polygon.compile_shader(gl, builder);
+    }
+
+    pub fn set_elem2vtx_vtx2xyz(
+        &mut self,
+        gl: &glow::Context,
+        elem_type: ElemType,
+        elem2vtx: &[usize],
+        vtx2xyz: &[f32],
+    ) -> Result<(), String> {
+        crate::validate::multiple_of("elem2vtx", elem2vtx.len(), elem_type.num_node())?;
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::index_range("elem2vtx", elem2vtx, vtx2xyz.len() / 3)?;
+        self.elem_type = elem_type;
+        self.elem2vtx = elem2vtx.to_vec();
+        self.vtx2xyz = vtx2xyz.to_vec();
+        self.elem2scalar = None;
+        self.update(gl)
+    }
+
+    /// color the elements by the scalar. `range` is mapped to the colormap (the min and the max
+    /// of the scalars if it is `None`)
+    pub fn set_elem2scalar(
+        &mut self,
+        gl: &glow::Context,
+        elem2scalar: &[f32],
+        range: Option<[f32; 2]>,
+    ) -> Result<(), String> {
+        crate::validate::length("elem2scalar", elem2scalar.len(), self.num_elem())?;
+        let range = range.unwrap_or_else(|| {
+            elem2scalar
+                .iter()
+                .fold([f32::MAX, f32::MIN], |r, &v| [r[0].min(v), r[1].max(v)])
+        });
+        self.elem2scalar = Some((elem2scalar.to_vec(), range));
+        self.update(gl)
+    }
+
+    pub fn num_elem(&self) -> usize {
+        self.elem2vtx.len() / self.elem_type.num_node()
+    }
+
+    /// element index of each polygon of [`Self::drawer_polygon`] (e.g., for the picking)
+    pub fn face2elem(&self) -> &[usize] {
+        &self.face2elem
+    }
+
+    fn node2xyz(&self, i_elem: usize) -> Vec<[f32; 3]> {
+        let num_node = self.elem_type.num_node();
+        self.elem2vtx[i_elem * num_node..(i_elem + 1) * num_node]
+            .iter()
+            .map(|&i_vtx| *arrayref::array_ref![self.vtx2xyz, i_vtx * 3, 3])
+            .collect()
+    }
+
+    /// signed distance from the cutting plane (positive on the removed side)
+    fn height(&self, p: &[f32; 3]) -> f32 {
+        match &self.plane {
+            Some((org, nrm)) => del_geo_core::vec3::dot(&del_geo_core::vec3::sub(p, org), nrm),
+            None => -1.0,
+        }
+    }
+
+    fn elem_color(&self, i_elem: usize) -> [f32; 3] {
+        match &self.elem2scalar {
+            Some((elem2scalar, [min, max])) => {
+                let t = if max > min {
+                    (elem2scalar[i_elem] - min) / (max - min)
+                } else {
+                    0.5
+                };
+                crate::colormap::jet(t)
+            }
+            None => self.color,
+        }
+    }
+
+    /// extract the faces for the current `shrink` and `plane` and upload them
+    pub fn update(&mut self, gl: &glow::Context) -> Result<(), String> {
+        let num_elem = self.num_elem();
+        let elem2node2xyz: Vec<Vec<[f32; 3]>> = (0..num_elem).map(|i| self.node2xyz(i)).collect();
+        let elem2centroid: Vec<[f32; 3]> = elem2node2xyz.iter().map(|ps| centroid(ps)).collect();
+        let elem2is_kept: Vec<bool> = elem2centroid
+            .iter()
+            .map(|c| self.height(c) <= 0.0)
+            .collect();
+        let faces = if self.shrink >= 1.0 {
+            boundary_faces(self.elem_type, &self.elem2vtx, &elem2is_kept)
+        } else {
+            let num_face = self.elem_type.faces().len();
+            (0..num_elem)
+                .filter(|&i| elem2is_kept[i])
+                .flat_map(|i_elem| (0..num_face).map(move |i_face| (i_elem, i_face)))
+                .collect()
+        };
+        //
+        let mut elem2idx = vec![0usize];
+        let mut poly2xyz = Vec::<f32>::new();
+        let mut face2rgb = Vec::<f32>::new();
+        self.face2elem.clear();
+        for (i_elem, i_face) in faces {
+            let c = &elem2centroid[i_elem];
+            for &i_node in self.elem_type.faces()[i_face] {
+                let q = shrink_toward(&elem2node2xyz[i_elem][i_node], c, self.shrink);
+                poly2xyz.extend_from_slice(&q);
+            }
+            elem2idx.push(poly2xyz.len() / 3);
+            face2rgb.extend_from_slice(&self.elem_color(i_elem));
+            self.face2elem.push(i_elem);
+        }
+        // cross-section of the elements crossing the plane
+        if let Some((_, nrm)) = self.plane {
+            for (i_elem, node2xyz) in elem2node2xyz.iter().enumerate() {
+                let node2height: Vec<f32> = node2xyz.iter().map(|p| self.height(p)).collect();
+                let section = cross_section(self.elem_type, node2xyz, &node2height, &nrm);
+                if section.len() < 3 {
+                    continue;
+                }
+                let c = centroid(&section);
+                for p in &section {
+                    poly2xyz.extend_from_slice(&shrink_toward(p, &c, self.shrink));
+                }
+                elem2idx.push(poly2xyz.len() / 3);
+                face2rgb.extend_from_slice(&self.elem_color(i_elem));
+                self.face2elem.push(i_elem);
+            }
+        }
+        // clip the kept elements crossing the plane, slightly beyond it to keep the cross-section
+        let clip_plane = self.plane.map(|(org, nrm)| {
+            let size = self.vtx2xyz.iter().fold(0f32, |s, v| s.max(v.abs()));
+            let eps = 1.0e-4 * size.max(1.0e-20);
+            let d = -del_geo_core::vec3::dot(&org, &nrm) - eps * del_geo_core::vec3::norm(&nrm);
+            [nrm[0], nrm[1], nrm[2], d]
+        });
+        self.drawer_polygon.set_clip_plane(clip_plane);
+        let idx2vtx: Vec<usize> = (0..poly2xyz.len() / 3).collect();
+        self.drawer_polygon
+            .set_elem2idx_idx2vtx_vtx2xyz(gl, &elem2idx, &idx2vtx, &poly2xyz)?;
+        self.drawer_polygon.set_elem2rgb(gl, &face2rgb)
+    }
+
+    pub fn draw(&self, gl: &glow::Context) {
+        self.drawer_polygon.draw(gl);
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.drawer_polygon.destroy(gl);
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn centroid(ps: &[[f32; 3]]) -> [f32; 3] {
+    let c = ps
+        .iter()
+        .fold([0f32; 3], |c, p| del_geo_core::vec3::add(&c, p));
+    del_geo_core::vec3::scale(&c, 1.0 / ps.len() as f32)
+}
+
+/// `p` moved toward `c` by the ratio `shrink` (1.0 keeps `p` and 0.0 moves it to `c`)
+fn shrink_toward(p: &[f32; 3], c: &[f32; 3], shrink: f32) -> [f32; 3] {
+    del_geo_core::vec3::axpy(shrink, &del_geo_core::vec3::sub(p, c), c)
+}
+
+/// faces `(i_elem, i_face)` of the kept elements that are not shared by two kept elements.
+/// The faces are identified by their sorted vertex indices
+fn boundary_faces(
+    elem_type: ElemType,
+    elem2vtx: &[usize],
+    elem2is_kept: &[bool],
+) -> Vec<(usize, usize)> {
+    let num_node = elem_type.num_node();
+    let face_key = |i_elem: usize, face: &[usize]| {
+        let mut key: Vec<usize> = face
+            .iter()
+            .map(|&i_node| elem2vtx[i_elem * num_node + i_node])
+            .collect();
+        key.sort();
+        key
+    };
+    let kept = || (0..elem2is_kept.len()).filter(|&i| elem2is_kept[i]);
+    let mut face2count = std::collections::HashMap::<Vec<usize>, usize>::new();
+    for i_elem in kept() {
+        for face in elem_type.faces() {
+            *face2count.entry(face_key(i_elem, face)).or_insert(0) += 1;
+        }
+    }
+    kept()
+        .flat_map(|i_elem| {
+            elem_type
+                .faces()
+                .iter()
+                .enumerate()
+                .map(move |f| (i_elem, f))
+        })
+        .filter(|&(i_elem, (_, face))| face2count[&face_key(i_elem, face)] == 1)
+        .map(|(i_elem, (i_face, _))| (i_elem, i_face))
+        .collect()
+}
+
+/// polygon of the intersection of an element with the plane of the normal `nrm`, sorted by
+/// the angle around its center. `node2height` is the signed distance of the nodes from the
+/// plane. The nodes on the plane are included once, not once per edge through them
+fn cross_section(
+    elem_type: ElemType,
+    node2xyz: &[[f32; 3]],
+    node2height: &[f32],
+    nrm: &[f32; 3],
+) -> Vec<[f32; 3]> {
+    let mut section: Vec<[f32; 3]> = (0..node2xyz.len())
+        .filter(|&i_node| node2height[i_node] == 0.0)
+        .map(|i_node| node2xyz[i_node])
+        .collect();
+    for &[i0, i1] in elem_type.edges() {
+        let (h0, h1) = (node2height[i0], node2height[i1]);
+        if !((h0 < 0.0 && h1 > 0.0) || (h0 > 0.0 && h1 < 0.0)) {
+            continue;
+        }
+        let (p0, p1) = (&node2xyz[i0], &node2xyz[i1]);
+        let d = del_geo_core::vec3::sub(p1, p0);
+        section.push(del_geo_core::vec3::axpy(h0 / (h0 - h1), &d, p0));
+    }
+    if section.len() < 3 {
+        return section;
+    }
+    let (bx, by) = del_geo_core::vec3::basis_xy_from_basis_z(nrm);
+    let c = centroid(&section);
+    let angle = |p: &[f32; 3]| {
+        let d = del_geo_core::vec3::sub(p, &c);
+        del_geo_core::vec3::dot(&d, &by).atan2(del_geo_core::vec3::dot(&d, &bx))
+    };
+    section.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNIT_CUBE: [[f32; 3]; 8] = [
+        [0., 0., 0.],
+        [1., 0., 0.],
+        [1., 1., 0.],
+        [0., 1., 0.],
+        [0., 0., 1.],
+        [1., 0., 1.],
+        [1., 1., 1.],
+        [0., 1., 1.],
+    ];
+
+    #[test]
+    fn test_boundary_faces() {
+        // two tetrahedra sharing the face (1,2,3)
+        let elem2vtx = [0, 1, 2, 3, 4, 1, 3, 2];
+        let faces = boundary_faces(ElemType::Tet, &elem2vtx, &[true, true]);
+        assert_eq!(faces.len(), 6);
+        // the shared face is the face 0 of the first element and the face 0 of the second
+        assert!(!faces.contains(&(0, 0)));
+        assert!(!faces.contains(&(1, 0)));
+        // the face becomes the boundary if the other element is removed
+        let faces = boundary_faces(ElemType::Tet, &elem2vtx, &[true, false]);
+        assert_eq!(faces, vec![(0, 0), (0, 1), (0, 2), (0, 3)]);
+        //
+        let elem2vtx: Vec<usize> = (0..8).collect();
+        let faces = boundary_faces(ElemType::Hex, &elem2vtx, &[true]);
+        assert_eq!(faces.len(), 6);
+    }
+
+    #[test]
+    fn test_shrink_toward() {
+        let c = centroid(&UNIT_CUBE);
+        assert_eq!(c, [0.5, 0.5, 0.5]);
+        assert_eq!(shrink_toward(&UNIT_CUBE[0], &c, 1.0), UNIT_CUBE[0]);
+        assert_eq!(shrink_toward(&UNIT_CUBE[0], &c, 0.5), [0.25, 0.25, 0.25]);
+        assert_eq!(shrink_toward(&UNIT_CUBE[6], &c, 0.0), c);
+    }
+
+    #[test]
+    fn test_cross_section() {
+        // the hexahedron cut at z=0.5 gives the square in the order around it
+        let node2height: Vec<f32> = UNIT_CUBE.iter().map(|p| p[2] - 0.5).collect();
+        let section = cross_section(ElemType::Hex, &UNIT_CUBE, &node2height, &[0., 0., 1.]);
+        assert_eq!(section.len(), 4);
+        for i in 0..4 {
+            let (p, q) = (&section[i], &section[(i + 1) % 4]);
+            assert!((p[2] - 0.5).abs() < 1.0e-6);
+            let d = del_geo_core::vec3::norm(&del_geo_core::vec3::sub(p, q));
+            assert!((d - 1.0).abs() < 1.0e-6, "not adjacent: {p:?} {q:?}");
+        }
+        // the node 0 is on the plane, the nodes 1 and 2 are above and the node 3 is below
+        let node2xyz = [[0., 0., 0.], [1., 0., 1.], [0., 1., 1.], [0., 0., -1.]];
+        let node2height: Vec<f32> = node2xyz.iter().map(|p| p[2]).collect();
+        let section = cross_section(ElemType::Tet, &node2xyz, &node2height, &[0., 0., 1.]);
+        assert_eq!(section.len(), 3);
+        assert_eq!(section.iter().filter(|p| **p == [0., 0., 0.]).count(), 1);
+        // only touching the plane at a node
+        let node2xyz = [[0., 0., 0.], [1., 0., 1.], [0., 1., 1.], [0., 0., 1.]];
+        let node2height: Vec<f32> = node2xyz.iter().map(|p| p[2]).collect();
+        let section = cross_section(ElemType::Tet, &node2xyz, &node2height, &[0., 0., 1.]);
+        assert!(section.len() < 3);
+    }
+}
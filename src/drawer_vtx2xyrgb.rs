@@ -1,107 +1,192 @@
+//! draw the primitives of the vertices with the per-vertex color.
+//! The vertices are either 2D (`xyrgb`) or 3D (`xyzrgb`) interleaved with the color
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform bool isNdc; // the position is in the normalized device coordinate
+layout (location = 0) in vec3 xyzIn; // z is 0 for the 2D vertices
+layout (location = 1) in vec3 rgbIn;
+out vec3 v_color;
+void main() {
+    v_color = rgbIn;
+    if( isNdc ){
+        gl_Position = vec4(xyzIn, 1.0);
+    } else {
+        gl_Position = frameMatPrj * frameMatView * vec4(xyzIn, 1.0);
+    }
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+in vec3 v_color;
+out vec4 out_color;
+void main() {
+    out_color = vec4(v_color, 1.0);
+}
+"#;
+
 pub struct Drawer {
-    pub program: Option<glow::Program>,
+    program: Option<crate::shader_program::ShaderProgram>,
+    /// `glow::POINTS`, `glow::LINES`, `glow::LINE_STRIP`, `glow::TRIANGLES`,
+    /// `glow::TRIANGLE_STRIP` or `glow::TRIANGLE_FAN`
     pub mode: u32,
-    pub vertex_array: Option<glow::VertexArray>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    vbo: Option<glow::NativeBuffer>,
+    /// dimension of the position (2 or 3)
+    ndim: usize,
+    num_vtx: usize,
+    /// size of the buffer in bytes
+    capacity: usize,
 }
 
 impl Drawer {
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
+    pub fn new() -> Self {
+        Drawer {
+            program: None,
+            mode: glow::TRIANGLES,
+            vertex_array: None,
+            vbo: None,
+            ndim: 2,
+            num_vtx: 0,
+            capacity: 0,
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+            self.vbo = Some(gl.create_buffer().unwrap());
+        }
+        self.set_program(gl, program);
+    }
+
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// 2D vertices. Error if the length is not a multiple of 5 or before
+    /// [`Drawer::compile_shader`]
+    pub fn set_vtx2xyrgb(&mut self, gl: &glow::Context, vtx2xyrgb: &[f32]) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyrgb", vtx2xyrgb.len(), 5)?;
+        self.upload(gl, vtx2xyrgb, 2)
+    }
+
+    /// 3D vertices. Error if the length is not a multiple of 6 or before
+    /// [`Drawer::compile_shader`]
+    pub fn set_vtx2xyzrgb(&mut self, gl: &glow::Context, vtx2xyzrgb: &[f32]) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyzrgb", vtx2xyzrgb.len(), 6)?;
+        self.upload(gl, vtx2xyzrgb, 3)
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.ndim
+    }
 
-        let (vertex_shader_source, fragment_shader_source) = (
-            r#"
-                in vec2 xyzIn;
-                in vec3 rgbIn;
-                out vec3 v_color;
-                void main() {
-                    v_color = rgbIn;
-                    gl_Position = vec4(xyzIn, 0.0, 1.0);
-                }
-            "#,
-            r#"
-                precision mediump float;
-                in vec3 v_color;
-                out vec4 out_color;
-                void main() {
-                    out_color = vec4(v_color, 1.0);
-                }
-            "#,
-        );
-        let program = crate::compile_shaders(
-            gl,
-            shader_version,
-            vertex_shader_source,
-            fragment_shader_source,
-        );
-        self.program = program;
+    pub fn num_vtx(&self) -> usize {
+        self.num_vtx
     }
-    pub fn set_vtx2xyrgb(&mut self, gl: &glow::Context, vtx2xyrgb: &[f32]) {
-        use glow::HasContext as _;
+
+    /// the buffer is reused if it is large enough, so this can be called every frame
+    fn upload(&mut self, gl: &glow::Context, data: &[f32], ndim: usize) -> Result<(), String> {
+        if self.program.is_none() || self.vertex_array.is_none() {
+            return Err("vtx2xyrgb: the shader is not compiled yet".to_string());
+        }
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let stride = ndim + 3;
+        self.num_vtx = data.len() / stride;
         unsafe {
-            let vbo = gl.create_buffer().expect("Cannot create buffer");
-            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
-            gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(vtx2xyrgb),
-                glow::STATIC_DRAW,
-            );
+            gl.bind_vertex_array(self.vertex_array);
+            gl.bind_buffer(glow::ARRAY_BUFFER, self.vbo);
+            if bytes.len() <= self.capacity {
+                gl.buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, bytes);
+            } else {
+                gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, bytes, glow::DYNAMIC_DRAW);
+                self.capacity = bytes.len();
+            }
+            self.set_attrib(gl, ndim);
+            gl.bind_vertex_array(None);
+        }
+        self.ndim = ndim;
+        Ok(())
+    }
 
-            gl.use_program(self.program);
-
-            let vertex_array = gl
-                .create_vertex_array()
-                .expect("Cannot create vertex array");
-            gl.bind_vertex_array(Some(vertex_array));
-            //
-            let loc_xyz = gl
-                .get_attrib_location(self.program.unwrap(), "xyzIn")
-                .unwrap();
-            let loc_rgb = gl
-                .get_attrib_location(self.program.unwrap(), "rgbIn")
-                .unwrap();
-            dbg!(loc_xyz, loc_rgb);
-            //
-            gl.vertex_attrib_pointer_f32(
-                loc_xyz,
-                2,
-                glow::FLOAT,
-                false,
-                5 * std::mem::size_of::<f32>() as i32,
-                0,
-            );
+    unsafe fn set_attrib(&self, gl: &glow::Context, ndim: usize) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        let stride = ((ndim + 3) * std::mem::size_of::<f32>()) as i32;
+        for (name, size, offset) in [("xyzIn", ndim, 0), ("rgbIn", 3, ndim)] {
+            let Some(loc) = program.attrib_location(name) else {
+                // optimized out by the driver as it does not contribute to the output
+                continue;
+            };
             gl.vertex_attrib_pointer_f32(
-                loc_rgb,
-                3,
+                loc,
+                size as i32,
                 glow::FLOAT,
                 false,
-                5 * std::mem::size_of::<f32>() as i32,
-                2 * std::mem::size_of::<f32>() as i32,
+                stride,
+                (offset * std::mem::size_of::<f32>()) as i32,
             );
-            gl.enable_vertex_attrib_array(loc_xyz);
-            gl.enable_vertex_attrib_array(loc_rgb);
-            //
-            self.vertex_array = Some(vertex_array);
+            gl.enable_vertex_attrib_array(loc);
         }
     }
 
-    pub fn destroy(&self, gl: &glow::Context) {
-        use glow::HasContext as _;
-        unsafe {
-            gl.delete_program(self.program.unwrap());
-            gl.delete_vertex_array(self.vertex_array.unwrap());
-        }
+    /// draw with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        self.draw_impl(gl, false);
     }
 
+    /// draw the vertices given in the normalized device coordinate
     pub fn paint(&self, gl: &glow::Context) {
-        use glow::HasContext as _;
+        self.draw_impl(gl, true);
+    }
+
+    fn draw_impl(&self, gl: &glow::Context, is_ndc: bool) {
+        let Some(program) = &self.program else {
+            return;
+        };
         unsafe {
-            gl.use_program(self.program);
+            gl.use_program(Some(program.program));
             gl.bind_vertex_array(self.vertex_array);
-            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            program.set_bool(gl, "isNdc", is_ndc).unwrap();
+            gl.draw_arrays(self.mode, 0, self.num_vtx as i32);
+            gl.bind_vertex_array(None);
         }
     }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(program) = self.program.take() {
+                program.destroy(gl);
+            }
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            if let Some(vbo) = self.vbo.take() {
+                gl.delete_buffer(vbo);
+            }
+        }
+    }
+}
+
+impl Default for Drawer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -1,9 +1,50 @@
+//! draw the vertices as the points or the polyline. The color and the point size are either
+//! uniform or per vertex, and a subset of the vertices can be highlighted with a different style
+
+use glow::HasContext;
+
+pub const VS_SRC: &str = r#"
+#include "frame_uniforms"
+uniform vec3 color;
+uniform float pointSize;
+uniform bool hasVtxColor;
+uniform bool hasVtxSize;
+layout (location = 0) in vec3 xyzIn;
+layout (location = 1) in vec3 rgbIn;
+layout (location = 2) in float sizeIn;
+out vec3 v_color;
+void main() {
+    gl_Position = frameMatPrj * frameMatView * vec4(xyzIn, 1.0);
+    gl_PointSize = hasVtxSize ? sizeIn : pointSize;
+    v_color = hasVtxColor ? rgbIn : color;
+}
+"#;
+
+pub const FS_SRC: &str = r#"
+in vec3 v_color;
+out vec4 out_color;
+void main() {
+    out_color = vec4(v_color, 1.0);
+}
+"#;
+
 pub struct Drawer {
-    pub program: Option<glow::Program>,
-    uniform_loc_mvp: Option<glow::NativeUniformLocation>,
+    pub program: Option<crate::shader_program::ShaderProgram>,
     num_vtx: usize,
+    /// `glow::POINTS`, `glow::LINES`, `glow::LINE_STRIP` or `glow::LINE_LOOP`
     pub mode: u32,
     pub vertex_array: Option<glow::VertexArray>,
+    /// color of the vertices without the per-vertex color
+    pub color: [f32; 3],
+    /// size in pixels of the points without the per-vertex size
+    pub point_size: f32,
+    /// color and point size of the highlighted vertices
+    pub highlight: ([f32; 3], f32),
+    vbo_xyz: Option<glow::NativeBuffer>,
+    vbo_rgb: Option<glow::NativeBuffer>,
+    vbo_size: Option<glow::NativeBuffer>,
+    /// indices of the highlighted vertices
+    ebo_highlight: Option<(glow::NativeBuffer, crate::index_buffer::IndexType, usize)>,
 }
 
 impl Default for Drawer {
@@ -16,104 +57,243 @@ impl Drawer {
     pub fn new() -> Self {
         Drawer {
             program: None,
-            mode: 0,
+            mode: glow::POINTS,
             vertex_array: None,
-            uniform_loc_mvp: None,
             num_vtx: 0,
+            color: [1.0, 0.0, 0.0],
+            point_size: 5.0,
+            highlight: ([1.0, 1.0, 0.0], 10.0),
+            vbo_xyz: None,
+            vbo_rgb: None,
+            vbo_size: None,
+            ebo_highlight: None,
         }
     }
-    pub fn compile_shader(&mut self, gl: &glow::Context) {
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let program = builder.build(gl, VS_SRC, FS_SRC, &[]).unwrap();
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+        }
+        self.set_program(gl, program);
+    }
 
-        let (vertex_shader_source, fragment_shader_source) = (
-            r#"
-                uniform mat4 Mvp;
-                in vec3 xyzIn;
-                out vec3 v_color;
-                void main() {
-                    gl_Position = Mvp * vec4(xyzIn, 1.0);
-                    gl_PointSize = 5.0;
-                    v_color = vec3(1.0, 0.0, 0.0);
-                }
-            "#,
-            r#"
-                precision mediump float;
-                in vec3 v_color;
-                out vec4 out_color;
-                void main() {
-                    out_color = vec4(v_color, 1.0);
-                }
-            "#,
-        );
-        let program = crate::compile_shaders(
-            gl,
-            shader_version,
-            vertex_shader_source,
-            fragment_shader_source,
-        );
-        self.program = program;
+    /// replace the program (e.g., the one re-compiled by [`crate::shader_hot_reload`]
+    /// from [`VS_SRC`] and [`FS_SRC`]). The previous program is released
+    pub fn set_program(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program.replace(program) {
+            program0.destroy(gl);
+        }
     }
-    pub fn set_vtx2xyz(&mut self, gl: &glow::Context, vtx2xyz: &[f32]) {
-        self.num_vtx = vtx2xyz.len() / 3;
-        use glow::HasContext as _;
-        unsafe {
-            let vertex_array = gl
-                .create_vertex_array()
-                .expect("Cannot create vertex array");
-            let vbo = gl.create_buffer().expect("Cannot create buffer");
 
-            gl.bind_vertex_array(Some(vertex_array));
-            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+    /// upload `data` to the buffer (created if it is `None`) bound to the attribute `name`.
+    /// Error before [`Drawer::compile_shader`]
+    fn set_attrib(
+        &self,
+        gl: &glow::Context,
+        vbo: &mut Option<glow::NativeBuffer>,
+        name: &str,
+        size: i32,
+        data: &[f32],
+    ) -> Result<(), String> {
+        let Some(program) = &self.program else {
+            return Err(format!("{name}: the shader is not compiled yet"));
+        };
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            let vbo0 = *vbo.get_or_insert_with(|| gl.create_buffer().unwrap());
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo0));
             gl.buffer_data_u8_slice(
                 glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(vtx2xyz),
-                glow::STATIC_DRAW,
+                bytemuck::cast_slice(data),
+                glow::DYNAMIC_DRAW,
             );
-            //gl.enable_vertex_attrib_array(0);
+            if let Some(loc) = program.attrib_location(name) {
+                gl.vertex_attrib_pointer_f32(
+                    loc,
+                    size,
+                    glow::FLOAT,
+                    false,
+                    size * std::mem::size_of::<f32>() as i32,
+                    0,
+                );
+                gl.enable_vertex_attrib_array(loc);
+            }
+            gl.bind_vertex_array(None);
+        }
+        Ok(())
+    }
+
+    /// delete the buffer and disable the attribute `name`. Error before [`Drawer::compile_shader`]
+    /// if there is the buffer
+    fn delete_attrib(
+        &self,
+        gl: &glow::Context,
+        vbo: &mut Option<glow::NativeBuffer>,
+        name: &str,
+    ) -> Result<(), String> {
+        let Some(vbo0) = *vbo else {
+            return Ok(());
+        };
+        let Some(program) = &self.program else {
+            return Err(format!("{name}: the shader is not compiled yet"));
+        };
+        unsafe {
+            gl.bind_vertex_array(self.vertex_array);
+            if let Some(loc) = program.attrib_location(name) {
+                gl.disable_vertex_attrib_array(loc);
+            }
+            gl.bind_vertex_array(None);
+            gl.delete_buffer(vbo0);
+        }
+        *vbo = None;
+        Ok(())
+    }
 
-            gl.use_program(self.program);
+    /// error if the length is not a multiple of 3 or (with [`crate::validate::IS_ENABLED`])
+    /// a coordinate is not finite. The per-vertex color and size and the highlight are reset if the number of vertices changes
+    pub fn set_vtx2xyz(&mut self, gl: &glow::Context, vtx2xyz: &[f32]) -> Result<(), String> {
+        crate::validate::multiple_of("vtx2xyz", vtx2xyz.len(), 3)?;
+        crate::validate::finite("vtx2xyz", vtx2xyz)?;
+        if vtx2xyz.len() / 3 != self.num_vtx {
+            self.set_vtx2rgb(gl, None)?;
+            self.set_vtx2size(gl, None)?;
+            self.set_highlight(gl, &[])?;
+        }
+        let mut vbo = self.vbo_xyz.take();
+        let result = self.set_attrib(gl, &mut vbo, "xyzIn", 3, vtx2xyz);
+        self.vbo_xyz = vbo;
+        result?;
+        self.num_vtx = vtx2xyz.len() / 3;
+        Ok(())
+    }
 
-            //
-            self.uniform_loc_mvp = gl.get_uniform_location(self.program.unwrap(), "Mvp");
-            let loc_xyz = gl
-                .get_attrib_location(self.program.unwrap(), "xyzIn")
-                .unwrap();
-            dbg!(loc_xyz);
-            //
-            gl.vertex_attrib_pointer_f32(
-                loc_xyz,
-                3,
-                glow::FLOAT,
-                false,
-                3 * std::mem::size_of::<f32>() as i32,
-                0,
-            );
-            gl.enable_vertex_attrib_array(loc_xyz);
-            //
-            self.vertex_array = Some(vertex_array);
+    /// per-vertex color. `None` to use `color`
+    pub fn set_vtx2rgb(
+        &mut self,
+        gl: &glow::Context,
+        vtx2rgb: Option<&[f32]>,
+    ) -> Result<(), String> {
+        if let Some(vtx2rgb) = vtx2rgb {
+            crate::validate::length("vtx2rgb", vtx2rgb.len(), self.num_vtx * 3)?;
+        }
+        let mut vbo = self.vbo_rgb.take();
+        let result = match vtx2rgb {
+            Some(vtx2rgb) => self.set_attrib(gl, &mut vbo, "rgbIn", 3, vtx2rgb),
+            None => self.delete_attrib(gl, &mut vbo, "rgbIn"),
+        };
+        self.vbo_rgb = vbo;
+        result
+    }
+
+    /// per-vertex point size in pixels. `None` to use `point_size`
+    pub fn set_vtx2size(
+        &mut self,
+        gl: &glow::Context,
+        vtx2size: Option<&[f32]>,
+    ) -> Result<(), String> {
+        if let Some(vtx2size) = vtx2size {
+            crate::validate::length("vtx2size", vtx2size.len(), self.num_vtx)?;
         }
+        let mut vbo = self.vbo_size.take();
+        let result = match vtx2size {
+            Some(vtx2size) => self.set_attrib(gl, &mut vbo, "sizeIn", 1, vtx2size),
+            None => self.delete_attrib(gl, &mut vbo, "sizeIn"),
+        };
+        self.vbo_size = vbo;
+        result
     }
 
-    pub fn destroy(&self, gl: &glow::Context) {
-        use glow::HasContext as _;
+    /// vertices drawn as the points with the style `highlight` on top of the others.
+    /// Empty for no highlight
+    pub fn set_highlight(&mut self, gl: &glow::Context, vtxs: &[usize]) -> Result<(), String> {
+        crate::validate::index_range("highlight", vtxs, self.num_vtx)?;
+        if let Some((ebo, _, _)) = self.ebo_highlight.take() {
+            unsafe { gl.delete_buffer(ebo) };
+        }
+        if vtxs.is_empty() {
+            return Ok(());
+        }
+        if self.vertex_array.is_none() {
+            return Err("highlight: the shader is not compiled yet".to_string());
+        }
+        let index_data = crate::index_buffer::IndexData::new(vtxs, None)?;
         unsafe {
-            gl.delete_program(self.program.unwrap());
-            gl.delete_vertex_array(self.vertex_array.unwrap());
+            gl.bind_vertex_array(self.vertex_array);
+            let ebo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+            index_data.upload(gl, glow::DYNAMIC_DRAW);
+            gl.bind_vertex_array(None);
+            self.ebo_highlight = Some((ebo, index_data.index_type, index_data.num_index));
         }
+        Ok(())
     }
 
-    pub fn draw(&self, gl: &glow::Context, mvp: &[f32; 16]) {
-        use glow::HasContext as _;
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(program) = self.program.take() {
+            program.destroy(gl);
+        }
         unsafe {
-            gl.use_program(self.program);
-            gl.uniform_matrix_4_f32_slice(self.uniform_loc_mvp.as_ref(), false, mvp);
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+            for vbo in [
+                self.vbo_xyz.take(),
+                self.vbo_rgb.take(),
+                self.vbo_size.take(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                gl.delete_buffer(vbo);
+            }
+            if let Some((ebo, _, _)) = self.ebo_highlight.take() {
+                gl.delete_buffer(ebo);
+            }
+        }
+        self.num_vtx = 0;
+    }
+
+    /// draw with the camera of [`crate::frame_uniforms`]
+    pub fn draw(&self, gl: &glow::Context) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        unsafe {
+            gl.use_program(Some(program.program));
+            program.set_vec3(gl, "color", &self.color).unwrap();
+            program
+                .set_f32(gl, "pointSize", &[self.point_size])
+                .unwrap();
+            let has_vtx_color = self.vbo_rgb.is_some();
+            program.set_bool(gl, "hasVtxColor", has_vtx_color).unwrap();
+            let has_vtx_size = self.vbo_size.is_some();
+            program.set_bool(gl, "hasVtxSize", has_vtx_size).unwrap();
             gl.enable(glow::PROGRAM_POINT_SIZE);
             gl.bind_vertex_array(self.vertex_array);
-            gl.draw_arrays(glow::POINTS, 0, self.num_vtx as i32);
+            gl.draw_arrays(self.mode, 0, self.num_vtx as i32);
+            if let Some((ebo, index_type, num_index)) = self.ebo_highlight {
+                let (rgb, size) = &self.highlight;
+                program.set_vec3(gl, "color", rgb).unwrap();
+                program.set_f32(gl, "pointSize", &[*size]).unwrap();
+                program.set_bool(gl, "hasVtxColor", false).unwrap();
+                program.set_bool(gl, "hasVtxSize", false).unwrap();
+                // drawn over the same vertices drawn above. The depth function of the caller
+                // is restored after it
+                let depth_func = gl.get_parameter_i32(glow::DEPTH_FUNC) as u32;
+                gl.depth_func(glow::LEQUAL);
+                gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+                gl.draw_elements(glow::POINTS, num_index as i32, index_type.gl_type(), 0);
+                gl.depth_func(depth_func);
+            }
+            gl.bind_vertex_array(None);
         }
     }
 }
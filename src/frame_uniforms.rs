@@ -0,0 +1,178 @@
+//! per-frame uniform buffer object (UBO) for the camera and the lighting
+//!
+//! Upload the camera and the lighting once per frame with [`FrameUniformBuffer::update`] and
+//! declare the block [`GLSL_BLOCK`] (or `#include "frame_uniforms"` with
+//! [`crate::shader_builder::ShaderBuilder`]) in the shaders. The block of a program is
+//! connected to the buffer by [`bind_block`].
+//!
+//! The built-in drawers read the view and the projection (and the viewport size and the light)
+//! from the block, so `draw` does not take the matrices. Only the local transformation of a
+//! sub-mesh (e.g., [`crate::drawer_elem2vtx_vtx2xyz::Drawer::set_transform`]) and the flip of z
+//! are uploaded per draw call. Update the buffer again for a pass with another camera
+//! (e.g., with [`crate::shadow_map::ShadowMap::frame_uniforms`] for the shadow pass).
+
+use glow::HasContext;
+
+/// name of the uniform block in GLSL
+pub const BLOCK_NAME: &str = "FrameUniforms";
+
+/// binding point of the uniform buffer
+pub const BINDING: u32 = 0;
+
+/// std140 declaration of the block and the projection with it.
+/// The layout must match [`FrameUniforms::to_std140`]
+pub const GLSL_BLOCK: &str = r#"
+layout (std140) uniform FrameUniforms {
+    mat4 frameMatView;
+    mat4 frameMatPrj;
+    vec4 frameViewportTime; // (width, height, time, scale of z of the flipped drawing)
+    vec4 frameLightDir;     // (direction toward the light, intensity)
+    vec4 frameLightColor;   // (color, ambient)
+};
+
+// clip coordinate of the point in the view coordinate. The z is flipped for the drawers
+// drawing with the flipped depth order (`is_z_flip`) unless the frame disables it
+vec4 frame_project(vec4 pos_view, bool is_z_flip) {
+    vec4 q = frameMatPrj * pos_view;
+    if( is_z_flip ){ q.z *= frameViewportTime.w; }
+    return q;
+}
+"#;
+
+/// number of `f32` in the std140 block
+const NUM_F32: usize = 16 + 16 + 4 + 4 + 4;
+
+#[derive(Clone, Debug)]
+pub struct FrameUniforms {
+    pub mat_view: [f32; 16],
+    pub mat_projection: [f32; 16],
+    /// size of the viewport in pixels
+    pub viewport_size: [f32; 2],
+    /// time in seconds for the animation
+    pub time: f32,
+    /// unit direction toward the light in the world coordinate
+    pub light_dir: [f32; 3],
+    pub light_intensity: f32,
+    pub light_color: [f32; 3],
+    pub ambient: f32,
+    /// the drawers drawing with the flipped depth order (e.g., [`crate::drawer_uv_layout`])
+    /// flip the z of `mat_projection` if true. False for the pass whose projection has the
+    /// depth order of those drawers (e.g., [`crate::shadow_map::ShadowMap::frame_uniforms`])
+    pub is_z_flip: bool,
+}
+
+impl Default for FrameUniforms {
+    fn default() -> Self {
+        FrameUniforms {
+            mat_view: del_geo_core::mat4_col_major::from_identity(),
+            mat_projection: del_geo_core::mat4_col_major::from_identity(),
+            viewport_size: [1.0, 1.0],
+            time: 0.0,
+            light_dir: [0.0, 0.0, 1.0],
+            light_intensity: 1.0,
+            light_color: [1.0, 1.0, 1.0],
+            ambient: 0.2,
+            is_z_flip: true,
+        }
+    }
+}
+
+impl FrameUniforms {
+    /// data of the block in the std140 layout
+    pub fn to_std140(&self) -> [f32; NUM_F32] {
+        let mut data = [0f32; NUM_F32];
+        data[0..16].copy_from_slice(&self.mat_view);
+        data[16..32].copy_from_slice(&self.mat_projection);
+        data[32..36].copy_from_slice(&[
+            self.viewport_size[0],
+            self.viewport_size[1],
+            self.time,
+            if self.is_z_flip { -1.0 } else { 1.0 },
+        ]);
+        data[36..39].copy_from_slice(&self.light_dir);
+        data[39] = self.light_intensity;
+        data[40..43].copy_from_slice(&self.light_color);
+        data[43] = self.ambient;
+        data
+    }
+}
+
+pub struct FrameUniformBuffer {
+    ubo: Option<glow::NativeBuffer>,
+}
+
+impl FrameUniformBuffer {
+    pub fn new() -> Self {
+        FrameUniformBuffer { ubo: None }
+    }
+
+    pub fn init(&mut self, gl: &glow::Context) {
+        unsafe {
+            let ubo = gl.create_buffer().unwrap();
+            gl.bind_buffer(glow::UNIFORM_BUFFER, Some(ubo));
+            gl.buffer_data_size(
+                glow::UNIFORM_BUFFER,
+                (NUM_F32 * std::mem::size_of::<f32>()) as i32,
+                glow::DYNAMIC_DRAW,
+            );
+            gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+            self.ubo = Some(ubo);
+        }
+    }
+
+    /// upload the uniforms and bind the buffer to [`BINDING`]. Call once per frame
+    pub fn update(&self, gl: &glow::Context, uniforms: &FrameUniforms) {
+        let data = uniforms.to_std140();
+        unsafe {
+            gl.bind_buffer(glow::UNIFORM_BUFFER, self.ubo);
+            gl.buffer_sub_data_u8_slice(glow::UNIFORM_BUFFER, 0, bytemuck::cast_slice(&data));
+            gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+            gl.bind_buffer_base(glow::UNIFORM_BUFFER, BINDING, self.ubo);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(ubo) = self.ubo.take() {
+            unsafe {
+                gl.delete_buffer(ubo);
+            }
+        }
+    }
+}
+
+impl Default for FrameUniformBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// connect the block [`BLOCK_NAME`] of the program to [`BINDING`].
+/// Returns false if the program does not use the block
+pub fn bind_block(gl: &glow::Context, program: glow::NativeProgram) -> bool {
+    unsafe {
+        let Some(index) = gl.get_uniform_block_index(program, BLOCK_NAME) else {
+            return false;
+        };
+        gl.uniform_block_binding(program, index, BINDING);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_to_std140() {
+        let mut uniforms = super::FrameUniforms {
+            viewport_size: [640.0, 480.0],
+            time: 2.5,
+            ..Default::default()
+        };
+        let data = uniforms.to_std140();
+        assert_eq!(data[0..16], uniforms.mat_view);
+        assert_eq!(data[32..36], [640.0, 480.0, 2.5, -1.0]);
+        assert_eq!(data[36..40], [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(data[40..44], [1.0, 1.0, 1.0, 0.2]);
+        uniforms.is_z_flip = false;
+        assert_eq!(uniforms.to_std140()[35], 1.0);
+    }
+}
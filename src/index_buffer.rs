@@ -0,0 +1,146 @@
+//! element (index) buffer stored with the smallest integer type that holds the indices
+//!
+//! The largest value of each type is excluded because WebGL2 (and GLES3 with
+//! `PRIMITIVE_RESTART_FIXED_INDEX`) always treats it as the primitive restart index
+
+use glow::HasContext;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexType {
+    U8,
+    U16,
+    U32,
+}
+
+impl IndexType {
+    /// smallest type that can store `max_index`
+    pub fn fit(max_index: u32) -> Self {
+        if max_index < u8::MAX as u32 {
+            IndexType::U8
+        } else if max_index < u16::MAX as u32 {
+            IndexType::U16
+        } else {
+            IndexType::U32
+        }
+    }
+
+    /// largest index that can be stored, i.e., one less than the primitive restart index
+    pub fn max_value(&self) -> u32 {
+        match self {
+            IndexType::U8 => u8::MAX as u32 - 1,
+            IndexType::U16 => u16::MAX as u32 - 1,
+            IndexType::U32 => u32::MAX - 1,
+        }
+    }
+
+    /// `type` argument of `glDrawElements`
+    pub fn gl_type(&self) -> u32 {
+        match self {
+            IndexType::U8 => glow::UNSIGNED_BYTE,
+            IndexType::U16 => glow::UNSIGNED_SHORT,
+            IndexType::U32 => glow::UNSIGNED_INT,
+        }
+    }
+
+    pub fn size_of(&self) -> usize {
+        match self {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        }
+    }
+}
+
+/// indices packed into bytes of the given type
+pub struct IndexData {
+    pub index_type: IndexType,
+    pub bytes: Vec<u8>,
+    pub num_index: usize,
+    /// `None` for the empty indices
+    pub max_index: Option<u32>,
+}
+
+impl IndexData {
+    /// pack the indices with `index_type`, or with the smallest type if it is `None`.
+    /// Error if an index does not fit in `index_type`, or in `u32` if it is `None`
+    pub fn new<T>(elem2vtx: &[T], index_type: Option<IndexType>) -> Result<Self, String>
+    where
+        T: 'static + Copy + num_traits::AsPrimitive<u64>,
+    {
+        let max_index: Option<u64> = elem2vtx.iter().map(|i| (*i).as_()).max();
+        let index_type0 = index_type.unwrap_or(IndexType::U32);
+        if let Some(max_index) = max_index {
+            if max_index > index_type0.max_value() as u64 {
+                return Err(format!(
+                    "the index {max_index} does not fit in {index_type0:?}"
+                ));
+            }
+        }
+        // the indices fit in `u32` from here
+        let max_index = max_index.map(|i| i as u32);
+        let index_type = index_type.unwrap_or_else(|| IndexType::fit(max_index.unwrap_or(0)));
+        let bytes: Vec<u8> = match index_type {
+            IndexType::U8 => elem2vtx.iter().map(|i| (*i).as_() as u8).collect(),
+            IndexType::U16 => {
+                let a: Vec<u16> = elem2vtx.iter().map(|i| (*i).as_() as u16).collect();
+                bytemuck::cast_slice(&a).to_vec()
+            }
+            IndexType::U32 => {
+                let a: Vec<u32> = elem2vtx.iter().map(|i| (*i).as_() as u32).collect();
+                bytemuck::cast_slice(&a).to_vec()
+            }
+        };
+        Ok(IndexData {
+            index_type,
+            bytes,
+            num_index: elem2vtx.len(),
+            max_index,
+        })
+    }
+
+    /// upload to the buffer bound to `ELEMENT_ARRAY_BUFFER`
+    pub fn upload(&self, gl: &glow::Context, usage: u32) {
+        unsafe {
+            gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, &self.bytes, usage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(IndexType::fit(0), IndexType::U8);
+        assert_eq!(IndexType::fit(254), IndexType::U8);
+        // the largest values are the primitive restart index
+        assert_eq!(IndexType::fit(255), IndexType::U16);
+        assert_eq!(IndexType::fit(65534), IndexType::U16);
+        assert_eq!(IndexType::fit(65535), IndexType::U32);
+        for index_type in [IndexType::U8, IndexType::U16, IndexType::U32] {
+            assert_eq!(IndexType::fit(index_type.max_value()), index_type);
+        }
+    }
+
+    #[test]
+    fn test_index_data() {
+        let d = IndexData::new(&[0usize, 255, 3], None).unwrap();
+        assert_eq!(d.index_type, IndexType::U16);
+        assert_eq!((d.num_index, d.max_index), (3, Some(255)));
+        assert_eq!(d.bytes.len(), 6);
+        assert_eq!(&d.bytes[2..4], &255u16.to_ne_bytes());
+        //
+        let d = IndexData::new::<u32>(&[], None).unwrap();
+        assert_eq!(
+            (d.index_type, d.max_index, d.bytes.len()),
+            (IndexType::U8, None, 0)
+        );
+        //
+        assert!(IndexData::new(&[255u32], Some(IndexType::U8)).is_err());
+        assert!(IndexData::new(&[254u32], Some(IndexType::U8)).is_ok());
+        assert!(IndexData::new(&[u32::MAX], None).is_err());
+        #[cfg(target_pointer_width = "64")]
+        assert!(IndexData::new(&[u32::MAX as usize + 1], None).is_err());
+    }
+}
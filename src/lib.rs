@@ -1,17 +1,66 @@
+pub mod colormap;
+pub mod drawer_axes_gizmo;
+pub mod drawer_box3_wireframe;
+pub mod drawer_debug_overlay;
 pub mod drawer_edge2;
 pub mod drawer_elem2vtx_vtx2xyz;
 pub mod drawer_elem2vtx_vtx2xyz_vtx2uv;
+pub mod drawer_grid;
+pub mod drawer_ground_plane;
+pub mod drawer_image;
+pub mod drawer_measure;
+pub mod drawer_mesh;
 pub mod drawer_mesh2_at_multiple_loc2s;
+pub mod drawer_polygon_mesh;
+pub mod drawer_sdf2;
+pub mod drawer_silhouette_crease;
+pub mod drawer_text;
 pub mod drawer_tri2node2xyz_tri2node2rgb;
+pub mod drawer_uv_layout;
+pub mod drawer_volume_grid;
+pub mod drawer_volume_mesh;
 pub mod drawer_vtx2xyrgb;
 pub mod drawer_vtx2xyz;
+pub mod frame_uniforms;
+pub mod index_buffer;
+pub mod render_target;
+pub mod shader_builder;
+pub mod shader_hot_reload;
+pub mod shader_program;
+pub mod shadow_map;
+pub mod ssao;
+pub mod trimesh3_primitive;
+pub mod validate;
+pub mod vertex_layout;
 
+/// compile and link the program from the vertex and fragment shader sources.
+/// `shader_version` is prepended to both sources
+///
+/// # Panics
+/// if the compilation or the linking fails. Use [`try_compile_shaders`] to handle the error
 pub fn compile_shaders(
     gl: &glow::Context,
     shader_version: &str,
     vertex_shader_source: &str,
     fragment_shader_source: &str,
 ) -> Option<glow::NativeProgram> {
+    let program = try_compile_shaders(
+        gl,
+        shader_version,
+        vertex_shader_source,
+        fragment_shader_source,
+    )
+    .unwrap_or_else(|e| panic!("{e}"));
+    Some(program)
+}
+
+/// same as [`compile_shaders`] but returns the info log as the error instead of panicking
+pub fn try_compile_shaders(
+    gl: &glow::Context,
+    shader_version: &str,
+    vertex_shader_source: &str,
+    fragment_shader_source: &str,
+) -> Result<glow::NativeProgram, String> {
     use glow::HasContext;
     unsafe {
         let program = gl.create_program().expect("Cannot create program");
@@ -20,35 +69,43 @@ pub fn compile_shaders(
             (glow::FRAGMENT_SHADER, fragment_shader_source),
         ];
 
-        let shaders: Vec<_> = shader_sources
-            .iter()
-            .map(|(shader_type, shader_source)| {
-                let shader = gl
-                    .create_shader(*shader_type)
-                    .expect("Cannot create shader");
-                gl.shader_source(shader, &format!("{shader_version}\n{shader_source}"));
-                gl.compile_shader(shader);
-                assert!(
-                    gl.get_shader_compile_status(shader),
+        let mut shaders = Vec::with_capacity(shader_sources.len());
+        let mut error = None;
+        for (shader_type, shader_source) in shader_sources.iter() {
+            let shader = gl
+                .create_shader(*shader_type)
+                .expect("Cannot create shader");
+            gl.shader_source(shader, &format!("{shader_version}\n{shader_source}"));
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                error = Some(format!(
                     "Failed to compile {shader_type}: {}",
                     gl.get_shader_info_log(shader)
-                );
-                gl.attach_shader(program, shader);
-                shader
-            })
-            .collect();
+                ));
+                gl.delete_shader(shader);
+                break;
+            }
+            gl.attach_shader(program, shader);
+            shaders.push(shader);
+        }
 
-        gl.link_program(program);
-        assert!(
-            gl.get_program_link_status(program),
-            "{}",
-            gl.get_program_info_log(program)
-        );
+        if error.is_none() {
+            gl.link_program(program);
+            if !gl.get_program_link_status(program) {
+                error = Some(gl.get_program_info_log(program));
+            }
+        }
 
         for shader in shaders {
             gl.detach_shader(program, shader);
             gl.delete_shader(shader);
         }
-        Some(program)
+        match error {
+            Some(e) => {
+                gl.delete_program(program);
+                Err(e)
+            }
+            None => Ok(program),
+        }
     }
 }
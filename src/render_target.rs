@@ -0,0 +1,223 @@
+//! offscreen framebuffer with the color and the depth textures used by the passes rendering
+//! into a texture (e.g., [`crate::ssao`], [`crate::shadow_map`], [`crate::drawer_volume_grid`])
+//!
+//! [`RenderTarget::begin`] saves the framebuffer, the viewport and the scissor test bound
+//! before (i.e., the ones of egui) and [`RenderTarget::end`] restores them.
+//! The textures are (re-)allocated when the size is changed.
+
+use glow::HasContext;
+
+/// `(internal_format, format, type)` of a texture such as `(glow::RGBA8, glow::RGBA, glow::UNSIGNED_BYTE)`
+pub type TextureFormat = (u32, u32, u32);
+
+pub const FORMAT_RGBA8: TextureFormat = (glow::RGBA8, glow::RGBA, glow::UNSIGNED_BYTE);
+pub const FORMAT_R8: TextureFormat = (glow::R8, glow::RED, glow::UNSIGNED_BYTE);
+pub const FORMAT_DEPTH24: TextureFormat = (
+    glow::DEPTH_COMPONENT24,
+    glow::DEPTH_COMPONENT,
+    glow::UNSIGNED_INT,
+);
+
+pub struct RenderTarget {
+    /// formats of the color attachments `glow::COLOR_ATTACHMENT0 + i`
+    color_formats: Vec<TextureFormat>,
+    is_depth: bool,
+    framebuffer: Option<glow::NativeFramebuffer>,
+    colors: Vec<glow::NativeTexture>,
+    depth: Option<glow::NativeTexture>,
+    size: (i32, i32),
+    // state restored in `end`
+    prev_framebuffer: Option<glow::NativeFramebuffer>,
+    prev_viewport: [i32; 4],
+    prev_scissor_test: bool,
+}
+
+impl RenderTarget {
+    /// the textures are created at the first [`RenderTarget::begin`] or [`RenderTarget::resize`]
+    pub fn new(color_formats: &[TextureFormat], is_depth: bool) -> Self {
+        RenderTarget {
+            color_formats: color_formats.to_vec(),
+            is_depth,
+            framebuffer: None,
+            colors: vec![],
+            depth: None,
+            size: (0, 0),
+            prev_framebuffer: None,
+            prev_viewport: [0; 4],
+            prev_scissor_test: false,
+        }
+    }
+
+    pub fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// `i`-th color texture
+    pub fn color(&self, i: usize) -> Option<glow::NativeTexture> {
+        self.colors.get(i).copied()
+    }
+
+    pub fn depth(&self) -> Option<glow::NativeTexture> {
+        self.depth
+    }
+
+    pub fn framebuffer(&self) -> Option<glow::NativeFramebuffer> {
+        self.framebuffer
+    }
+
+    /// (re-)allocate the textures if the size is changed. The binding of the framebuffer is kept
+    pub fn resize(&mut self, gl: &glow::Context, width: i32, height: i32) {
+        if self.size == (width, height) && self.framebuffer.is_some() {
+            return;
+        }
+        self.delete_buffers(gl);
+        self.size = (width, height);
+        unsafe {
+            let prev_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            let fbo = gl.create_framebuffer().unwrap();
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            for (i, format) in self.color_formats.iter().enumerate() {
+                let tex = create_texture(gl, width, height, format);
+                let attachment = glow::COLOR_ATTACHMENT0 + i as u32;
+                gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    attachment,
+                    glow::TEXTURE_2D,
+                    Some(tex),
+                    0,
+                );
+                self.colors.push(tex);
+            }
+            if self.is_depth {
+                let tex = create_texture(gl, width, height, &FORMAT_DEPTH24);
+                gl.framebuffer_texture_2d(
+                    glow::FRAMEBUFFER,
+                    glow::DEPTH_ATTACHMENT,
+                    glow::TEXTURE_2D,
+                    Some(tex),
+                    0,
+                );
+                self.depth = Some(tex);
+            }
+            if self.colors.is_empty() {
+                // depth only
+                gl.draw_buffers(&[glow::NONE]);
+                gl.read_buffer(glow::NONE);
+            } else {
+                let draw_buffers: Vec<u32> = (0..self.colors.len() as u32)
+                    .map(|i| glow::COLOR_ATTACHMENT0 + i)
+                    .collect();
+                gl.draw_buffers(&draw_buffers);
+            }
+            assert_eq!(
+                gl.check_framebuffer_status(glow::FRAMEBUFFER),
+                glow::FRAMEBUFFER_COMPLETE,
+                "offscreen framebuffer is incomplete"
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, prev_framebuffer);
+            self.framebuffer = Some(fbo);
+        }
+    }
+
+    /// save the current framebuffer, viewport and scissor test, then bind this framebuffer
+    /// of the size of the current viewport
+    pub fn begin(&mut self, gl: &glow::Context) {
+        let mut viewport = [0i32; 4];
+        unsafe {
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+        }
+        self.begin_with_size(gl, viewport[2], viewport[3]);
+    }
+
+    /// same as [`RenderTarget::begin`] but with the given size (e.g., of the shadow map)
+    pub fn begin_with_size(&mut self, gl: &glow::Context, width: i32, height: i32) {
+        unsafe {
+            self.prev_framebuffer = gl.get_parameter_framebuffer(glow::FRAMEBUFFER_BINDING);
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut self.prev_viewport);
+            self.prev_scissor_test = gl.is_enabled(glow::SCISSOR_TEST);
+            // the scissor box set by egui is in the window coordinate
+            gl.disable(glow::SCISSOR_TEST);
+        }
+        self.resize(gl, width, height);
+        self.bind(gl);
+    }
+
+    /// bind the framebuffer and set the viewport to its size without saving the state
+    pub fn bind(&self, gl: &glow::Context) {
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, self.framebuffer);
+            gl.viewport(0, 0, self.size.0, self.size.1);
+        }
+    }
+
+    /// restore the framebuffer, the viewport and the scissor test saved in [`RenderTarget::begin`]
+    pub fn end(&self, gl: &glow::Context) {
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, self.prev_framebuffer);
+            let v = &self.prev_viewport;
+            gl.viewport(v[0], v[1], v[2], v[3]);
+            if self.prev_scissor_test {
+                gl.enable(glow::SCISSOR_TEST);
+            }
+        }
+    }
+
+    fn delete_buffers(&mut self, gl: &glow::Context) {
+        unsafe {
+            if let Some(fbo) = self.framebuffer.take() {
+                gl.delete_framebuffer(fbo);
+            }
+            for tex in self.colors.drain(..).chain(self.depth.take()) {
+                gl.delete_texture(tex);
+            }
+        }
+        self.size = (0, 0);
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.delete_buffers(gl);
+    }
+}
+
+/// true if the 32-bit float textures can be sampled with `glow::LINEAR`. Always on the desktop
+/// OpenGL, and only with the extension `OES_texture_float_linear` on GLES and WebGL
+pub fn is_f32_linear_filterable(gl: &glow::Context) -> bool {
+    !gl.version().is_embedded
+        || gl
+            .supported_extensions()
+            .iter()
+            .any(|ext| ext.ends_with("OES_texture_float_linear"))
+}
+
+/// texture with the nearest sampling and the clamped boundary.
+/// The depth textures are only filterable with `glow::NEAREST` on GLES
+unsafe fn create_texture(
+    gl: &glow::Context,
+    width: i32,
+    height: i32,
+    &(internal_format, format, ty): &TextureFormat,
+) -> glow::NativeTexture {
+    let tex = gl.create_texture().unwrap();
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        width,
+        height,
+        0,
+        format,
+        ty,
+        glow::PixelUnpackData::Slice(None),
+    );
+    for (param, value) in [
+        (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+        (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+        (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+        (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+    ] {
+        gl.tex_parameter_i32(glow::TEXTURE_2D, param, value as i32);
+    }
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    tex
+}
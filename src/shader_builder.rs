@@ -0,0 +1,344 @@
+//! shader preprocessor on top of [`crate::compile_shaders`]
+//!
+//! * selects the version header and the precision qualifiers for the target
+//! * resolves `#include "name"` of the registered snippets (see [`SNIPPETS`])
+//! * injects `#define` for the feature permutations
+//! * caches the compiled program for each permutation
+//!
+//! Share one builder among the drawers (i.e., pass it to their `compile_shader`) so that the
+//! drawers with the same sources share the program. The cache does not own the programs:
+//! each drawer destroys its [`ShaderProgram`] handle, and the program is deleted with the
+//! last handle. A program whose handles are all destroyed is compiled again when requested.
+
+use crate::shader_program::ShaderProgram;
+
+/// GLSL dialect of the context
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlslTarget {
+    /// OpenGL 3.3 core profile
+    Gl33Core,
+    /// OpenGL ES 3.0
+    Gles30,
+    /// WebGL2 (GLSL ES 3.00)
+    WebGl2,
+}
+
+impl GlslTarget {
+    /// target of this build: WebGL2 for `wasm32` and OpenGL 3.3 otherwise
+    pub fn current() -> Self {
+        if cfg!(target_arch = "wasm32") {
+            GlslTarget::WebGl2
+        } else {
+            GlslTarget::Gl33Core
+        }
+    }
+
+    pub fn version_header(&self) -> &'static str {
+        match self {
+            GlslTarget::Gl33Core => "#version 330",
+            GlslTarget::Gles30 | GlslTarget::WebGl2 => "#version 300 es",
+        }
+    }
+
+    /// default precision qualifiers. GLSL ES requires the precision of `float` in the fragment shader
+    pub fn precision_header(&self) -> &'static str {
+        match self {
+            GlslTarget::Gl33Core => "",
+            GlslTarget::Gles30 | GlslTarget::WebGl2 => {
+                "precision highp float;\nprecision highp int;\nprecision highp sampler3D;\n"
+            }
+        }
+    }
+}
+
+/// shared snippets available with `#include "name"`
+pub const SNIPPETS: &[(&str, &str)] = &[
+    ("transform", SNIPPET_TRANSFORM),
+    ("lighting", SNIPPET_LIGHTING),
+    ("colormap", SNIPPET_COLORMAP),
+    ("vertex_id", SNIPPET_VERTEX_ID),
+    ("depth", SNIPPET_DEPTH),
+    ("antialias", SNIPPET_ANTIALIAS),
+    ("normal_output", SNIPPET_NORMAL_OUTPUT),
+    ("frame_uniforms", crate::frame_uniforms::GLSL_BLOCK),
+];
+
+const SNIPPET_TRANSFORM: &str = r#"
+vec4 transform_position(mat4 mv, mat4 prj, vec3 p) {
+    return prj * mv * vec4(p, 1.0);
+}
+
+vec3 transform_normal(mat4 mv, vec3 n) {
+    return normalize(transpose(inverse(mat3(mv))) * n);
+}
+"#;
+
+const SNIPPET_LIGHTING: &str = r#"
+// `n`: unit normal, `l`: unit direction toward the light, `v`: unit direction toward the eye
+float lambert(vec3 n, vec3 l) {
+    return max(dot(n, l), 0.0);
+}
+
+float blinn_phong(vec3 n, vec3 l, vec3 v, float shininess) {
+    vec3 h = normalize(l + v);
+    return pow(max(dot(n, h), 0.0), shininess);
+}
+"#;
+
+const SNIPPET_COLORMAP: &str = r#"
+// `t` in [0,1]
+vec3 colormap_grayscale(float t) {
+    return vec3(clamp(t, 0.0, 1.0));
+}
+
+vec3 colormap_jet(float t) {
+    t = clamp(t, 0.0, 1.0);
+    return clamp(vec3(1.5 - abs(4.0 * t - 3.0), 1.5 - abs(4.0 * t - 2.0), 1.5 - abs(4.0 * t - 1.0)), 0.0, 1.0);
+}
+
+// polynomial fit of matplotlib's viridis
+vec3 colormap_viridis(float t) {
+    t = clamp(t, 0.0, 1.0);
+    const vec3 c0 = vec3(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
+    const vec3 c1 = vec3(0.1050930431085774, 1.404613529898575, 1.384590162594685);
+    const vec3 c2 = vec3(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
+    const vec3 c3 = vec3(-4.634230498983486, -5.799100973351585, -19.33244095627987);
+    const vec3 c4 = vec3(6.228269936347081, 14.17993336680509, 56.69055260068105);
+    const vec3 c5 = vec3(4.776384997670288, -13.74514537774601, -65.35303263337234);
+    const vec3 c6 = vec3(-5.435455855934631, 4.645852612178535, 26.3124352495832);
+    return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+}
+
+vec3 colormap_coolwarm(float t) {
+    t = clamp(t, 0.0, 1.0);
+    vec3 cool = vec3(0.23, 0.299, 0.754);
+    vec3 mid = vec3(0.865, 0.865, 0.865);
+    vec3 warm = vec3(0.706, 0.016, 0.15);
+    return t < 0.5 ? mix(cool, mid, 2.0 * t) : mix(mid, warm, 2.0 * t - 1.0);
+}
+
+// `i` is `crate::colormap::Colormap::glsl_index`
+vec3 colormap(int i, float t) {
+    if( i == 0 ){ return colormap_grayscale(t); }
+    if( i == 1 ){ return colormap_jet(t); }
+    if( i == 2 ){ return colormap_viridis(t); }
+    return colormap_coolwarm(t);
+}
+"#;
+
+const SNIPPET_VERTEX_ID: &str = r#"
+// one triangle covering the viewport drawn with 3 vertices without the vertex buffer.
+// The viewport is [0,1]^2 of the returned coordinate
+vec2 fullscreen_triangle_uv() {
+    return vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+}
+
+// corner in [0,1]^2 of the rectangle drawn as a triangle strip of 4 vertices
+vec2 rectangle_strip_uv() {
+    return vec2(float(gl_VertexID & 1), float((gl_VertexID >> 1) & 1));
+}
+"#;
+
+const SNIPPET_DEPTH: &str = r#"
+// window depth in [0,1] of the point `p` transformed by `mvp`
+float window_depth(mat4 mvp, vec3 p) {
+    vec4 q = mvp * vec4(p, 1.0);
+    return (q.z / q.w) * 0.5 + 0.5;
+}
+
+// point transformed back by `mvp_inv` from the texture coordinate `t` of the viewport and the window depth
+vec3 unproject(mat4 mvp_inv, vec2 t, float depth) {
+    vec4 p = mvp_inv * vec4(t * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    return p.xyz / p.w;
+}
+"#;
+
+const SNIPPET_ANTIALIAS: &str = r#"
+// coverage of a line of `width` pixels where `d` is the distance in pixels
+float line_coverage(float d, float width) {
+    return clamp(0.5 * width - d + 0.5, 0.0, 1.0);
+}
+
+// coverage of the isolines of `v` at the multiples of `spacing`, `width` pixels wide
+float isoline_coverage(float v, float spacing, float width) {
+    float w = v / spacing;
+    return line_coverage(abs(fract(w - 0.5) - 0.5) / fwidth(w), width);
+}
+"#;
+
+const SNIPPET_NORMAL_OUTPUT: &str = r#"
+// second output read by `crate::ssao`. It is ignored when the framebuffer has one color attachment.
+// The other output must be declared with `layout (location = 0)`
+layout (location = 1) out vec4 FragNormal;
+
+// write the normal in the view coordinate encoded in [0,1]. The alpha is 0 for a degenerate normal
+void write_normal(vec3 n) {
+    float l = length(n);
+    FragNormal = l > 1.0e-20 ? vec4(n / l * 0.5 + 0.5, 1.0) : vec4(0.0);
+}
+
+// normal of the flat face from the position in the view coordinate
+vec3 flat_normal(vec3 pos_view) {
+    return cross(dFdx(pos_view), dFdy(pos_view));
+}
+"#;
+
+pub struct ShaderBuilder {
+    pub target: GlslTarget,
+    includes: std::collections::HashMap<String, String>,
+    /// compiled programs keyed by the target and the preprocessed vertex and fragment shaders.
+    /// The handles do not keep the programs alive
+    cache: std::collections::HashMap<(GlslTarget, String, String), ShaderProgram>,
+}
+
+impl ShaderBuilder {
+    /// builder for [`GlslTarget::current`] with the built-in [`SNIPPETS`]
+    pub fn new() -> Self {
+        Self::with_target(GlslTarget::current())
+    }
+
+    pub fn with_target(target: GlslTarget) -> Self {
+        let includes = SNIPPETS
+            .iter()
+            .map(|(name, src)| (name.to_string(), src.to_string()))
+            .collect();
+        ShaderBuilder {
+            target,
+            includes,
+            cache: std::collections::HashMap::new(),
+        }
+    }
+
+    /// register (or overwrite) a snippet that can be included with `#include "name"`
+    pub fn add_include(&mut self, name: &str, source: &str) {
+        self.includes.insert(name.to_string(), source.to_string());
+    }
+
+    /// resolve the includes recursively. A snippet is included only once
+    pub fn resolve_includes(&self, source: &str) -> Result<String, String> {
+        let mut included = std::collections::HashSet::<String>::new();
+        let mut stack = Vec::<String>::new();
+        self.resolve_includes_recursive(source, &mut included, &mut stack)
+    }
+
+    fn resolve_includes_recursive(
+        &self,
+        source: &str,
+        included: &mut std::collections::HashSet<String>,
+        stack: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let mut out = String::with_capacity(source.len());
+        for line in source.lines() {
+            let Some(rest) = line.trim_start().strip_prefix("#include") else {
+                out.push_str(line);
+                out.push('\n');
+                continue;
+            };
+            let name = rest
+                .trim()
+                .trim_matches(|c| c == '"' || c == '<' || c == '>');
+            if stack.iter().any(|s| s == name) {
+                return Err(format!("circular #include of \"{name}\""));
+            }
+            if !included.insert(name.to_string()) {
+                continue;
+            }
+            let Some(snippet) = self.includes.get(name) else {
+                return Err(format!("unknown #include \"{name}\""));
+            };
+            stack.push(name.to_string());
+            let resolved = self.resolve_includes_recursive(snippet, included, stack)?;
+            stack.pop();
+            out.push_str(&resolved);
+        }
+        Ok(out)
+    }
+
+    /// the source passed to the driver except the version header:
+    /// the precision qualifiers, the defines and the source with the includes resolved
+    pub fn preprocess(&self, source: &str, defines: &[(&str, &str)]) -> Result<String, String> {
+        let mut out = self.target.precision_header().to_string();
+        for (name, value) in defines {
+            out.push_str(&format!("#define {name} {value}\n"));
+        }
+        out.push_str(&self.resolve_includes(source)?);
+        Ok(out)
+    }
+
+    /// compile the program for the permutation given by `defines`,
+    /// or return another handle of the cached one if it is still alive.
+    /// The cache is keyed by the preprocessed sources, so overwriting a snippet with
+    /// [`ShaderBuilder::add_include`] compiles the program again.
+    /// The block of [`crate::frame_uniforms`] is connected if it is used
+    pub fn build(
+        &mut self,
+        gl: &glow::Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<ShaderProgram, String> {
+        let vs = self.preprocess(vertex_shader_source, defines)?;
+        let fs = self.preprocess(fragment_shader_source, defines)?;
+        let key = (self.target, vs, fs);
+        if let Some(program) = self.cache.get(&key).and_then(|p| p.upgrade()) {
+            return Ok(program);
+        }
+        let program = crate::try_compile_shaders(gl, self.target.version_header(), &key.1, &key.2)?;
+        crate::frame_uniforms::bind_block(gl, program);
+        let program = ShaderProgram::from_program(gl, program);
+        self.cache.insert(key, program.downgrade());
+        Ok(program)
+    }
+
+    /// forget the programs whose handles are all destroyed
+    pub fn purge(&mut self) {
+        self.cache.retain(|_, program| program.num_handle() > 0);
+    }
+}
+
+impl Default for ShaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_includes() {
+        let mut builder = ShaderBuilder::with_target(GlslTarget::Gl33Core);
+        builder.add_include("a", "float a;\n");
+        builder.add_include("b", "#include \"a\"\nfloat b;\n");
+        // nested includes are resolved and each snippet is included once
+        let src = builder
+            .resolve_includes("#include \"b\"\n  #include \"a\"\nvoid main() {}\n")
+            .unwrap();
+        assert_eq!(src, "float a;\nfloat b;\nvoid main() {}\n");
+        // overwriting a snippet changes the resolved source
+        builder.add_include("a", "float a2;\n");
+        let src = builder.resolve_includes("#include <b>\n").unwrap();
+        assert_eq!(src, "float a2;\nfloat b;\n");
+        //
+        assert!(builder.resolve_includes("#include \"c\"\n").is_err());
+        builder.add_include("c", "#include \"d\"\n");
+        builder.add_include("d", "#include \"c\"\n");
+        assert!(builder.resolve_includes("#include \"c\"\n").is_err());
+    }
+
+    #[test]
+    fn test_builtin_snippets() {
+        let builder = ShaderBuilder::with_target(GlslTarget::WebGl2);
+        for (name, _) in SNIPPETS {
+            assert!(builder
+                .resolve_includes(&format!("#include \"{name}\"\n"))
+                .is_ok());
+        }
+        let src = builder
+            .preprocess("void main() {}\n", &[("N", "3")])
+            .unwrap();
+        assert!(src.starts_with("precision highp float;\n"));
+        assert!(src.contains("#define N 3\n"));
+    }
+}
@@ -0,0 +1,124 @@
+//! re-compile the shaders loaded from files when they are modified (for the development)
+//!
+//! The modification is detected by polling the modification time of the files, so call
+//! [`HotReload::poll`] every frame. When the compilation fails the info log is kept in
+//! [`HotReload::last_error`] and the caller keeps using the previous program.
+//! The drawers expose their embedded sources (e.g., `VS_SRC` and `FS_SRC`) and `set_program`
+//! to replace their program with the re-compiled one.
+//!
+//! ```ignore
+//! // at the initialization
+//! let mut hot_reload = del_glow::shader_hot_reload::HotReload::new("mesh.vert", "mesh.frag");
+//! hot_reload.export_if_missing(
+//!     del_glow::drawer_elem2vtx_vtx2xyz::VS_SRC,
+//!     del_glow::drawer_elem2vtx_vtx2xyz::FS_SRC,
+//! )?;
+//! // in the paint callback
+//! if let Some(program) = hot_reload.poll(gl) {
+//!     drawer.set_program(gl, program);
+//! }
+//! ```
+
+pub struct HotReload {
+    pub path_vertex_shader: std::path::PathBuf,
+    pub path_fragment_shader: std::path::PathBuf,
+    /// the sources are passed to this builder to resolve `#include` and to add the headers
+    pub builder: crate::shader_builder::ShaderBuilder,
+    /// `#define` injected to both shaders
+    pub defines: Vec<(String, String)>,
+    /// info log of the last failed compilation. `None` after a successful compilation
+    pub last_error: Option<String>,
+    mtimes: Option<[std::time::SystemTime; 2]>,
+}
+
+impl HotReload {
+    pub fn new<P: AsRef<std::path::Path>>(path_vertex_shader: P, path_fragment_shader: P) -> Self {
+        HotReload {
+            path_vertex_shader: path_vertex_shader.as_ref().to_path_buf(),
+            path_fragment_shader: path_fragment_shader.as_ref().to_path_buf(),
+            builder: crate::shader_builder::ShaderBuilder::new(),
+            defines: vec![],
+            last_error: None,
+            mtimes: None,
+        }
+    }
+
+    /// write the (embedded) sources to the files if they do not exist yet,
+    /// so that they can be edited as the starting point
+    pub fn export_if_missing(
+        &self,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> std::io::Result<()> {
+        for (path, src) in [
+            (&self.path_vertex_shader, vertex_shader_source),
+            (&self.path_fragment_shader, fragment_shader_source),
+        ] {
+            if !path.exists() {
+                std::fs::write(path, src)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_mtimes(&self) -> std::io::Result<[std::time::SystemTime; 2]> {
+        let mtime_vs = std::fs::metadata(&self.path_vertex_shader)?.modified()?;
+        let mtime_fs = std::fs::metadata(&self.path_fragment_shader)?.modified()?;
+        Ok([mtime_vs, mtime_fs])
+    }
+
+    /// true if any of the files is modified since the last call (always true for the first call)
+    pub fn is_modified(&mut self) -> bool {
+        let Ok(mtimes) = self.read_mtimes() else {
+            return false;
+        };
+        if self.mtimes == Some(mtimes) {
+            return false;
+        }
+        self.mtimes = Some(mtimes);
+        true
+    }
+
+    /// read the files and compile them regardless of the modification
+    pub fn compile(
+        &mut self,
+        gl: &glow::Context,
+    ) -> Result<crate::shader_program::ShaderProgram, String> {
+        let read = |path: &std::path::Path| {
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))
+        };
+        let vs = read(&self.path_vertex_shader)?;
+        let fs = read(&self.path_fragment_shader)?;
+        let defines: Vec<(&str, &str)> = self
+            .defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let vs = self.builder.preprocess(&vs, &defines)?;
+        let fs = self.builder.preprocess(&fs, &defines)?;
+        let program =
+            crate::try_compile_shaders(gl, self.builder.target.version_header(), &vs, &fs)?;
+        crate::frame_uniforms::bind_block(gl, program);
+        Ok(crate::shader_program::ShaderProgram::from_program(
+            gl, program,
+        ))
+    }
+
+    /// compile the program if the files are modified. Returns the new program on success.
+    /// On failure, the error is kept in `last_error` (e.g., to show it in the UI)
+    pub fn poll(&mut self, gl: &glow::Context) -> Option<crate::shader_program::ShaderProgram> {
+        if !self.is_modified() {
+            return None;
+        }
+        match self.compile(gl) {
+            Ok(program) => {
+                self.last_error = None;
+                Some(program)
+            }
+            Err(e) => {
+                self.last_error = Some(e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! program with the reflection of the active uniforms and attributes
+//!
+//! The driver removes the uniforms and attributes that do not contribute to the output
+//! (e.g., `texIn` when the texture is not sampled). The setters of [`ShaderProgram`] ignore
+//! such names instead of failing, while they reject a value of the wrong type.
+//!
+//! A [`ShaderProgram`] is a handle that can be shared (e.g., the program cached by
+//! [`crate::shader_builder::ShaderBuilder`] for the drawers compiled with the same sources).
+//! Each handle is destroyed once, and the GL program is deleted with the last handle.
+//! The uniforms are the state of the GL program, so the drawers sharing it set them before drawing.
+
+use glow::HasContext;
+
+pub struct UniformInfo {
+    pub location: Option<glow::NativeUniformLocation>,
+    /// GL type enum such as `glow::FLOAT_VEC3`
+    pub utype: u32,
+    /// number of the elements for an array, otherwise 1
+    pub size: i32,
+}
+
+pub struct AttributeInfo {
+    pub location: u32,
+    /// GL type enum such as `glow::FLOAT_VEC3`
+    pub atype: u32,
+    pub size: i32,
+}
+
+struct Reflection {
+    uniforms: std::collections::BTreeMap<String, UniformInfo>,
+    attributes: std::collections::BTreeMap<String, AttributeInfo>,
+}
+
+pub struct ShaderProgram {
+    pub program: glow::NativeProgram,
+    reflection: std::sync::Arc<Reflection>,
+    /// number of the handles of `program` not destroyed yet, shared by the clones
+    num_handle: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    /// true after [`ShaderProgram::destroy`], and for the non-owning handle
+    is_destroyed: std::sync::atomic::AtomicBool,
+}
+
+/// another handle of the same program
+impl Clone for ShaderProgram {
+    fn clone(&self) -> Self {
+        self.num_handle
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        ShaderProgram {
+            program: self.program,
+            reflection: self.reflection.clone(),
+            num_handle: self.num_handle.clone(),
+            is_destroyed: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+}
+
+impl ShaderProgram {
+    /// reflect the active uniforms and attributes of the linked `program`
+    pub fn from_program(gl: &glow::Context, program: glow::NativeProgram) -> Self {
+        let mut uniforms = std::collections::BTreeMap::new();
+        let mut attributes = std::collections::BTreeMap::new();
+        unsafe {
+            for i in 0..gl.get_active_uniforms(program) {
+                let Some(u) = gl.get_active_uniform(program, i) else {
+                    continue;
+                };
+                // the array `a` is reported as `a[0]`
+                let name = u.name.strip_suffix("[0]").unwrap_or(&u.name).to_string();
+                let location = gl.get_uniform_location(program, &name);
+                uniforms.insert(
+                    name,
+                    UniformInfo {
+                        location,
+                        utype: u.utype,
+                        size: u.size,
+                    },
+                );
+            }
+            for i in 0..gl.get_active_attributes(program) {
+                let Some(a) = gl.get_active_attribute(program, i) else {
+                    continue;
+                };
+                let Some(location) = gl.get_attrib_location(program, &a.name) else {
+                    continue; // built-in such as `gl_VertexID`
+                };
+                attributes.insert(
+                    a.name,
+                    AttributeInfo {
+                        location,
+                        atype: a.atype,
+                        size: a.size,
+                    },
+                );
+            }
+        }
+        Self::new(
+            program,
+            Reflection {
+                uniforms,
+                attributes,
+            },
+        )
+    }
+
+    fn new(program: glow::NativeProgram, reflection: Reflection) -> Self {
+        ShaderProgram {
+            program,
+            reflection: std::sync::Arc::new(reflection),
+            num_handle: std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(1)),
+            is_destroyed: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    /// handle that does not keep the program alive, and whose [`ShaderProgram::destroy`]
+    /// does nothing (e.g., for a cache)
+    pub fn downgrade(&self) -> Self {
+        ShaderProgram {
+            program: self.program,
+            reflection: self.reflection.clone(),
+            num_handle: self.num_handle.clone(),
+            is_destroyed: std::sync::atomic::AtomicBool::new(true),
+        }
+    }
+
+    /// another handle of the program, or `None` if all the handles are destroyed
+    /// (i.e., the GL program is deleted)
+    pub fn upgrade(&self) -> Option<Self> {
+        if self.num_handle.load(std::sync::atomic::Ordering::Relaxed) == 0 {
+            return None;
+        }
+        Some(self.clone())
+    }
+
+    /// compile, link and reflect
+    pub fn compile(
+        gl: &glow::Context,
+        shader_version: &str,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<Self, String> {
+        let program = crate::try_compile_shaders(
+            gl,
+            shader_version,
+            vertex_shader_source,
+            fragment_shader_source,
+        )?;
+        Ok(Self::from_program(gl, program))
+    }
+
+    pub fn uniforms(&self) -> impl Iterator<Item = (&str, &UniformInfo)> {
+        self.reflection
+            .uniforms
+            .iter()
+            .map(|(name, info)| (name.as_str(), info))
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &AttributeInfo)> {
+        self.reflection
+            .attributes
+            .iter()
+            .map(|(name, info)| (name.as_str(), info))
+    }
+
+    /// `None` if the uniform is not active
+    pub fn uniform(&self, name: &str) -> Option<&UniformInfo> {
+        self.reflection.uniforms.get(name)
+    }
+
+    /// `None` if the attribute is not active
+    pub fn attrib_location(&self, name: &str) -> Option<u32> {
+        self.reflection.attributes.get(name).map(|a| a.location)
+    }
+
+    /// location of the active uniform `name` if its type is one of `utypes` and it has at least
+    /// `num_elem` elements. `Ok(None)` if the uniform is not active
+    fn location_checked(
+        &self,
+        name: &str,
+        utypes: &[u32],
+        num_elem: usize,
+    ) -> Result<Option<&glow::NativeUniformLocation>, String> {
+        let Some(info) = self.reflection.uniforms.get(name) else {
+            return Ok(None);
+        };
+        if !utypes.contains(&info.utype) {
+            return Err(format!(
+                "uniform \"{name}\" is {} but set as {}",
+                type_name(info.utype),
+                type_name(utypes[0])
+            ));
+        }
+        if num_elem > info.size as usize {
+            return Err(format!(
+                "uniform \"{name}\" has {} elements but {num_elem} are set",
+                info.size
+            ));
+        }
+        Ok(info.location.as_ref())
+    }
+
+    /// set `int` or `bool` uniform (also sampler units)
+    pub fn set_i32(&self, gl: &glow::Context, name: &str, v: i32) -> Result<(), String> {
+        let utypes = [
+            glow::INT,
+            glow::BOOL,
+            glow::SAMPLER_2D,
+            glow::SAMPLER_3D,
+            glow::SAMPLER_CUBE,
+        ];
+        let loc = self.location_checked(name, &utypes, 1)?;
+        unsafe { gl.uniform_1_i32(loc, v) };
+        Ok(())
+    }
+
+    pub fn set_bool(&self, gl: &glow::Context, name: &str, v: bool) -> Result<(), String> {
+        let loc = self.location_checked(name, &[glow::BOOL, glow::INT], 1)?;
+        unsafe { gl.uniform_1_i32(loc, v as i32) };
+        Ok(())
+    }
+
+    /// set `float` uniform or `float[]` if `v.len() > 1`
+    pub fn set_f32(&self, gl: &glow::Context, name: &str, v: &[f32]) -> Result<(), String> {
+        let num_elem = num_elem(name, v, 1)?;
+        let loc = self.location_checked(name, &[glow::FLOAT], num_elem)?;
+        unsafe { gl.uniform_1_f32_slice(loc, v) };
+        Ok(())
+    }
+
+    /// set `vec2` uniform or `vec2[]` if `v.len() > 2`
+    pub fn set_vec2(&self, gl: &glow::Context, name: &str, v: &[f32]) -> Result<(), String> {
+        let num_elem = num_elem(name, v, 2)?;
+        let loc = self.location_checked(name, &[glow::FLOAT_VEC2], num_elem)?;
+        unsafe { gl.uniform_2_f32_slice(loc, v) };
+        Ok(())
+    }
+
+    /// set `vec3` uniform or `vec3[]` if `v.len() > 3`
+    pub fn set_vec3(&self, gl: &glow::Context, name: &str, v: &[f32]) -> Result<(), String> {
+        let num_elem = num_elem(name, v, 3)?;
+        let loc = self.location_checked(name, &[glow::FLOAT_VEC3], num_elem)?;
+        unsafe { gl.uniform_3_f32_slice(loc, v) };
+        Ok(())
+    }
+
+    /// set `vec4` uniform or `vec4[]` if `v.len() > 4`
+    pub fn set_vec4(&self, gl: &glow::Context, name: &str, v: &[f32]) -> Result<(), String> {
+        let num_elem = num_elem(name, v, 4)?;
+        let loc = self.location_checked(name, &[glow::FLOAT_VEC4], num_elem)?;
+        unsafe { gl.uniform_4_f32_slice(loc, v) };
+        Ok(())
+    }
+
+    /// set column-major `mat3` uniform
+    pub fn set_mat3(&self, gl: &glow::Context, name: &str, m: &[f32; 9]) -> Result<(), String> {
+        let loc = self.location_checked(name, &[glow::FLOAT_MAT3], 1)?;
+        unsafe { gl.uniform_matrix_3_f32_slice(loc, false, m) };
+        Ok(())
+    }
+
+    /// set column-major `mat4` uniform
+    pub fn set_mat4(&self, gl: &glow::Context, name: &str, m: &[f32; 16]) -> Result<(), String> {
+        let loc = self.location_checked(name, &[glow::FLOAT_MAT4], 1)?;
+        unsafe { gl.uniform_matrix_4_f32_slice(loc, false, m) };
+        Ok(())
+    }
+
+    /// release this handle. The GL program is deleted if this is the last handle.
+    /// Calling this again on the same handle does nothing
+    pub fn destroy(&self, gl: &glow::Context) {
+        if self.release() {
+            unsafe {
+                gl.delete_program(self.program);
+            }
+        }
+    }
+
+    /// mark this handle destroyed. True if the GL program is to be deleted
+    fn release(&self) -> bool {
+        use std::sync::atomic::Ordering;
+        if self.is_destroyed.swap(true, Ordering::Relaxed) {
+            return false;
+        }
+        self.num_handle.fetch_sub(1, Ordering::Relaxed) == 1
+    }
+
+    /// number of the handles of the program not destroyed yet
+    pub fn num_handle(&self) -> usize {
+        self.num_handle.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// number of the elements of `num_component` components in `v`.
+/// Error if `v` is empty or has a partial element
+fn num_elem(name: &str, v: &[f32], num_component: usize) -> Result<usize, String> {
+    if v.is_empty() || !v.len().is_multiple_of(num_component) {
+        return Err(format!(
+            "uniform \"{name}\" is set with {} values that are not a multiple of {num_component}",
+            v.len()
+        ));
+    }
+    Ok(v.len() / num_component)
+}
+
+/// GLSL name of the GL type enum used in the error message
+pub fn type_name(utype: u32) -> &'static str {
+    match utype {
+        glow::FLOAT => "float",
+        glow::FLOAT_VEC2 => "vec2",
+        glow::FLOAT_VEC3 => "vec3",
+        glow::FLOAT_VEC4 => "vec4",
+        glow::INT => "int",
+        glow::INT_VEC2 => "ivec2",
+        glow::INT_VEC3 => "ivec3",
+        glow::INT_VEC4 => "ivec4",
+        glow::UNSIGNED_INT => "uint",
+        glow::BOOL => "bool",
+        glow::FLOAT_MAT2 => "mat2",
+        glow::FLOAT_MAT3 => "mat3",
+        glow::FLOAT_MAT4 => "mat4",
+        glow::SAMPLER_2D => "sampler2D",
+        glow::SAMPLER_3D => "sampler3D",
+        glow::SAMPLER_CUBE => "samplerCube",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_num_elem() {
+        assert_eq!(super::num_elem("a", &[0.; 6], 3), Ok(2));
+        assert_eq!(super::num_elem("a", &[0.; 1], 1), Ok(1));
+        assert!(super::num_elem("a", &[0.; 5], 3).is_err());
+        assert!(super::num_elem("a", &[], 2).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_shared_handle() {
+        let program = glow::NativeProgram(std::num::NonZeroU32::new(1).unwrap());
+        let reflection = super::Reflection {
+            uniforms: Default::default(),
+            attributes: Default::default(),
+        };
+        let p0 = super::ShaderProgram::new(program, reflection);
+        let cached = p0.downgrade();
+        let p1 = cached.upgrade().unwrap();
+        assert_eq!(p0.num_handle(), 2);
+        // the non-owning handle never deletes the program
+        assert!(!cached.release());
+        // destroying the same handle twice is counted once
+        assert!(!p0.release());
+        assert!(!p0.release());
+        assert_eq!(p1.num_handle(), 1);
+        // the last handle deletes the program, which cannot be shared any more
+        assert!(p1.release());
+        assert!(cached.upgrade().is_none());
+    }
+}
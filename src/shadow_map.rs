@@ -0,0 +1,149 @@
+//! depth-only render from a directional light used for shadow mapping
+//!
+//! Any drawer participates in the shadow pass by being drawn between [`ShadowMap::begin`] and
+//! [`ShadowMap::end`] after the uniform buffer of [`crate::frame_uniforms`] is updated with
+//! [`ShadowMap::frame_uniforms`]. The drawers flipping z (e.g.,
+//! [`crate::drawer_elem2vtx_vtx2xyz_vtx2uv::Drawer::draw`]) do not flip it in this pass so that
+//! the depth is written in the same order. Only the depth is written because the framebuffer
+//! has no color attachment.
+
+use glow::HasContext;
+
+pub struct ShadowMap {
+    /// width and height of the square depth texture
+    pub resolution: usize,
+    /// depth offset subtracted before the comparison to avoid shadow acne
+    pub bias: f32,
+    /// half width of the percentage-closer filtering kernel in texels (0 means no filtering)
+    pub pcf_radius: i32,
+    /// direction in which the light travels (does not need to be normalized)
+    pub light_dir: [f32; 3],
+    /// center of the bounding sphere of the shadow casters
+    pub center: [f32; 3],
+    /// radius of the bounding sphere of the shadow casters
+    pub radius: f32,
+    /// depth only. The depth textures are only filterable with NEAREST on GLES, so the
+    /// percentage-closer filtering is done in the shader
+    target: crate::render_target::RenderTarget,
+}
+
+impl ShadowMap {
+    pub fn new() -> Self {
+        ShadowMap {
+            resolution: 2048,
+            bias: 0.005,
+            pcf_radius: 1,
+            light_dir: [-1.0, -2.0, -1.0],
+            center: [0.0; 3],
+            radius: 1.0,
+            target: crate::render_target::RenderTarget::new(&[], true),
+        }
+    }
+
+    /// create the framebuffer and the depth texture with the current `resolution`
+    pub fn init(&mut self, gl: &glow::Context) {
+        let resolution = self.resolution as i32;
+        self.target.resize(gl, resolution, resolution);
+    }
+
+    /// re-create the depth texture if `resolution` is changed
+    pub fn set_resolution(&mut self, gl: &glow::Context, resolution: usize) {
+        self.resolution = resolution;
+        self.init(gl);
+    }
+
+    /// set the bounding sphere of the objects casting shadow
+    pub fn set_scene_bound(&mut self, center: &[f32; 3], radius: f32) {
+        self.center = *center;
+        self.radius = radius;
+    }
+
+    /// view matrix of the light. The light looks along `light_dir` toward `center`
+    pub fn mat_view(&self) -> [f32; 16] {
+        use del_geo_core::vec3;
+        let z = vec3::normalize(&self.light_dir);
+        let (x, y) = vec3::basis_xy_from_basis_z(&z);
+        let c = &self.center;
+        #[rustfmt::skip]
+        let m = [
+            x[0], y[0], z[0], 0.,
+            x[1], y[1], z[1], 0.,
+            x[2], y[2], z[2], 0.,
+            -vec3::dot(&x, c), -vec3::dot(&y, c), -vec3::dot(&z, c), 1.,
+        ];
+        m
+    }
+
+    /// orthographic projection of the light that encloses the bounding sphere.
+    /// The depth grows with the distance from the light
+    pub fn mat_projection(&self) -> [f32; 16] {
+        let s = 1.0 / self.radius;
+        del_geo_core::mat4_col_major::from_diagonal(s, s, s, 1.0)
+    }
+
+    /// the uniforms of the shadow pass: the camera is the light and z is not flipped.
+    /// The lighting is the one of `frame` (e.g., the uniforms of the main pass)
+    pub fn frame_uniforms(
+        &self,
+        frame: &crate::frame_uniforms::FrameUniforms,
+    ) -> crate::frame_uniforms::FrameUniforms {
+        crate::frame_uniforms::FrameUniforms {
+            mat_view: self.mat_view(),
+            mat_projection: self.mat_projection(),
+            viewport_size: [self.resolution as f32; 2],
+            is_z_flip: false,
+            ..frame.clone()
+        }
+    }
+
+    /// transformation from the world coordinate to the texture coordinate and the depth in [0,1]
+    pub fn mat_world2shadow(&self) -> [f32; 16] {
+        #[rustfmt::skip]
+        let ndc2tex = [
+            0.5, 0.0, 0.0, 0.0,
+            0.0, 0.5, 0.0, 0.0,
+            0.0, 0.0, 0.5, 0.0,
+            0.5, 0.5, 0.5, 1.0,
+        ];
+        del_geo_core::mat4_col_major::mult_three_mats_col_major(
+            &ndc2tex,
+            &self.mat_projection(),
+            &self.mat_view(),
+        )
+    }
+
+    /// bind the shadow framebuffer and clear its depth. Draw the shadow casters after this
+    pub fn begin(&mut self, gl: &glow::Context) {
+        let resolution = self.resolution as i32;
+        self.target.begin_with_size(gl, resolution, resolution);
+        unsafe {
+            gl.enable(glow::DEPTH_TEST);
+            gl.depth_mask(true);
+            gl.clear(glow::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    /// restore the framebuffer, the viewport and the scissor test set before [`ShadowMap::begin`]
+    pub fn end(&self, gl: &glow::Context) {
+        self.target.end(gl);
+    }
+
+    /// bind the depth texture to the texture unit `glow::TEXTURE0 + unit`
+    pub fn bind_texture(&self, gl: &glow::Context, unit: u32) {
+        unsafe {
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(glow::TEXTURE_2D, self.target.depth());
+            gl.active_texture(glow::TEXTURE0);
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.target.destroy(gl);
+    }
+}
+
+impl Default for ShadowMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,378 @@
+//! screen-space ambient occlusion as a post-process
+//!
+//! The mesh drawers render into an offscreen color, normal and depth buffer between
+//! [`Ssao::begin`] and [`Ssao::end`]. The drawers including the `"normal_output"` snippet of
+//! [`crate::shader_builder`] write the normal in the view coordinate to the second color attachment
+//! (the shaders of [`crate::drawer_mesh`] can include it as well). Where the normal is degenerate,
+//! it is reconstructed from the depth buffer. The normal attachment is undefined for the shaders
+//! without the snippet, so draw such primitives (e.g., the grid) after [`Ssao::end`]. The occlusion is estimated by sampling a hemisphere
+//! around each pixel, blurred, and the darkened color is composited onto the framebuffer that was
+//! bound before [`Ssao::begin`] (i.e., the one of egui).
+
+use glow::HasContext;
+
+const MAX_NUM_SAMPLE: usize = 32;
+
+pub const VS_FULLSCREEN: &str = r#"
+#include "vertex_id"
+out vec2 uv;
+void main() {
+    uv = fullscreen_triangle_uv();
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+/// vertex shader of the occlusion passing the inverse of the projection of
+/// [`crate::frame_uniforms`] to [`FS_AO`]
+pub const VS_AO: &str = r#"
+#include "frame_uniforms"
+#include "vertex_id"
+out vec2 uv;
+flat out mat4 matPrjInv;
+void main() {
+    uv = fullscreen_triangle_uv();
+    matPrjInv = inverse(frameMatPrj);
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+pub const FS_AO: &str = r#"
+#include "frame_uniforms"
+#include "depth"
+uniform sampler2D depthTex;
+uniform sampler2D normalTex;
+uniform vec3 kernel[32];
+uniform int numSample;
+uniform float radius;
+uniform float bias;
+
+in vec2 uv;
+flat in mat4 matPrjInv;
+out vec4 FragColor;
+
+vec3 view_position(vec2 t, float depth) {
+    return unproject(matPrjInv, t, depth);
+}
+
+float hash(vec2 p) {
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+    float depth = texture(depthTex, uv).r;
+    if( depth >= 1.0 ){ // background
+        FragColor = vec4(1.0);
+        return;
+    }
+    vec3 p = view_position(uv, depth);
+    // direction toward the camera that works for both perspective and orthographic projections
+    vec3 to_eye = view_position(uv, 0.0) - view_position(uv, 1.0);
+    vec4 n_tex = texture(normalTex, uv);
+    // the normal written by the drawer, or the one reconstructed from the depth
+    vec3 n = n_tex.a > 0.5 ? n_tex.xyz * 2.0 - 1.0 : cross(dFdx(p), dFdy(p));
+    n = normalize(n);
+    if( dot(n, to_eye) < 0.0 ){ n = -n; }
+    // random rotation of the kernel around the normal
+    float theta = 6.2831853 * hash(gl_FragCoord.xy);
+    vec3 r = vec3(cos(theta), sin(theta), 0.0);
+    vec3 t = normalize(abs(dot(r, n)) > 0.99 ? cross(n, vec3(0.0, 0.0, 1.0)) : r - n * dot(r, n));
+    vec3 b = cross(n, t);
+    float occlusion = 0.0;
+    for(int i = 0; i < numSample; ++i){
+        vec3 s = p + radius * mat3(t, b, n) * kernel[i];
+        vec4 q = frameMatPrj * vec4(s, 1.0);
+        q.xyz /= q.w;
+        vec2 st = q.xy * 0.5 + 0.5;
+        float depth_sample = q.z * 0.5 + 0.5;
+        float depth_scene = texture(depthTex, st).r;
+        vec3 p_scene = view_position(st, depth_scene);
+        float range = smoothstep(0.0, 1.0, radius / max(distance(p, p_scene), 1.0e-5));
+        occlusion += (depth_scene < depth_sample - bias ? 1.0 : 0.0) * range;
+    }
+    float ao = 1.0 - occlusion / float(numSample);
+    // no occlusion for the singular projection
+    FragColor = vec4(vec3(ao >= 0.0 ? ao : 1.0), 1.0);
+}
+"#;
+
+pub const FS_BLUR: &str = r#"
+uniform sampler2D aoTex;
+uniform int blurRadius;
+
+in vec2 uv;
+out vec4 FragColor;
+
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(aoTex, 0));
+    float sum = 0.0;
+    float count = 0.0;
+    for(int i = -blurRadius; i <= blurRadius; ++i){
+        for(int j = -blurRadius; j <= blurRadius; ++j){
+            sum += texture(aoTex, uv + vec2(i, j) * texel).r;
+            count += 1.0;
+        }
+    }
+    FragColor = vec4(vec3(sum / count), 1.0);
+}
+"#;
+
+pub const FS_COMPOSITE: &str = r#"
+uniform sampler2D colorTex;
+uniform sampler2D depthTex;
+uniform sampler2D aoTex;
+uniform float strength;
+
+in vec2 uv;
+out vec4 FragColor;
+
+void main() {
+    vec4 c = texture(colorTex, uv);
+    float ao = texture(aoTex, uv).r;
+    FragColor = vec4(c.rgb * mix(1.0, ao, strength), c.a);
+    gl_FragDepth = texture(depthTex, uv).r;
+}
+"#;
+
+pub struct Ssao {
+    /// radius of the sampling hemisphere in the view coordinate
+    pub radius: f32,
+    /// how much the color is darkened by the occlusion in [0,1]
+    pub strength: f32,
+    /// depth offset to avoid the self occlusion
+    pub bias: f32,
+    /// number of samples in the hemisphere (at most 32)
+    pub num_sample: usize,
+    /// half width of the box blur applied to the occlusion in pixels (0 means no blur)
+    pub blur_radius: i32,
+    //
+    program_ao: Option<crate::shader_program::ShaderProgram>,
+    program_blur: Option<crate::shader_program::ShaderProgram>,
+    program_composite: Option<crate::shader_program::ShaderProgram>,
+    vertex_array: Option<glow::NativeVertexArray>,
+    /// color, normal and depth of the scene
+    target_scene: crate::render_target::RenderTarget,
+    target_ao: crate::render_target::RenderTarget,
+    target_blur: crate::render_target::RenderTarget,
+    kernel: Vec<f32>,
+}
+
+impl Ssao {
+    pub fn new() -> Self {
+        use crate::render_target::{RenderTarget, FORMAT_R8, FORMAT_RGBA8};
+        Ssao {
+            radius: 0.1,
+            strength: 1.0,
+            bias: 1.0e-4,
+            num_sample: 16,
+            blur_radius: 2,
+            program_ao: None,
+            program_blur: None,
+            program_composite: None,
+            vertex_array: None,
+            target_scene: RenderTarget::new(&[FORMAT_RGBA8, FORMAT_RGBA8], true),
+            target_ao: RenderTarget::new(&[FORMAT_R8], false),
+            target_blur: RenderTarget::new(&[FORMAT_R8], false),
+            kernel: sample_hemisphere(MAX_NUM_SAMPLE),
+        }
+    }
+
+    pub fn compile_shader(
+        &mut self,
+        gl: &glow::Context,
+        builder: &mut crate::shader_builder::ShaderBuilder,
+    ) {
+        let mut build = |vs, fs| builder.build(gl, vs, fs, &[]).unwrap();
+        let program_ao = build(VS_AO, FS_AO);
+        let program_blur = build(VS_FULLSCREEN, FS_BLUR);
+        let program_composite = build(VS_FULLSCREEN, FS_COMPOSITE);
+        self.set_program_ao(gl, program_ao);
+        self.set_program_blur(gl, program_blur);
+        self.set_program_composite(gl, program_composite);
+        unsafe {
+            self.vertex_array = Some(gl.create_vertex_array().unwrap());
+        }
+    }
+
+    /// replace the program of the occlusion (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_AO`] and [`FS_AO`]). The previous program is released
+    pub fn set_program_ao(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program_ao.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// replace the program of the blur (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_FULLSCREEN`] and [`FS_BLUR`]). The previous program is released
+    pub fn set_program_blur(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program_blur.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// replace the program of the composition (e.g., the one re-compiled by
+    /// [`crate::shader_hot_reload`] from [`VS_FULLSCREEN`] and [`FS_COMPOSITE`]). The previous program is released
+    pub fn set_program_composite(
+        &mut self,
+        gl: &glow::Context,
+        program: crate::shader_program::ShaderProgram,
+    ) {
+        if let Some(program0) = self.program_composite.replace(program) {
+            program0.destroy(gl);
+        }
+    }
+
+    /// bind the offscreen buffer of the size of the current viewport and clear it.
+    /// Draw the meshes after this
+    pub fn begin(&mut self, gl: &glow::Context, clear_color: &[f32; 4]) {
+        self.target_scene.begin(gl);
+        let (w, h) = self.target_scene.size();
+        self.target_ao.resize(gl, w, h);
+        self.target_blur.resize(gl, w, h);
+        unsafe {
+            gl.depth_mask(true);
+            gl.clear_buffer_f32_slice(glow::COLOR, 0, clear_color);
+            // the alpha 0 marks the pixels without the normal
+            gl.clear_buffer_f32_slice(glow::COLOR, 1, &[0.0; 4]);
+            gl.clear_buffer_f32_slice(glow::DEPTH, 0, &[1.0]);
+        }
+    }
+
+    /// compute the occlusion and composite the result onto the framebuffer bound before
+    /// [`Ssao::begin`]. The projection of [`crate::frame_uniforms`] must be the one used to draw
+    /// the meshes. The meshes are composited without the occlusion if the projection is singular
+    pub fn end(&self, gl: &glow::Context) {
+        let (Some(program_ao), Some(program_blur), Some(program_composite)) = (
+            &self.program_ao,
+            &self.program_blur,
+            &self.program_composite,
+        ) else {
+            self.target_scene.end(gl);
+            return;
+        };
+        unsafe {
+            // state restored at the end
+            let is_depth_test = gl.is_enabled(glow::DEPTH_TEST);
+            let is_blend = gl.is_enabled(glow::BLEND);
+            let depth_func = gl.get_parameter_i32(glow::DEPTH_FUNC) as u32;
+            gl.disable(glow::DEPTH_TEST);
+            gl.disable(glow::BLEND);
+            gl.bind_vertex_array(self.vertex_array);
+            // occlusion
+            {
+                let program = program_ao;
+                self.target_ao.bind(gl);
+                gl.use_program(Some(program.program));
+                gl.active_texture(glow::TEXTURE0);
+                gl.bind_texture(glow::TEXTURE_2D, self.target_scene.depth());
+                gl.active_texture(glow::TEXTURE1);
+                gl.bind_texture(glow::TEXTURE_2D, self.target_scene.color(1));
+                program.set_i32(gl, "depthTex", 0).unwrap();
+                program.set_i32(gl, "normalTex", 1).unwrap();
+                program.set_vec3(gl, "kernel", &self.kernel).unwrap();
+                let num_sample = self.num_sample.clamp(1, MAX_NUM_SAMPLE) as i32;
+                program.set_i32(gl, "numSample", num_sample).unwrap();
+                program.set_f32(gl, "radius", &[self.radius]).unwrap();
+                program.set_f32(gl, "bias", &[self.bias]).unwrap();
+                gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                gl.active_texture(glow::TEXTURE0);
+            }
+            // blur
+            {
+                let program = program_blur;
+                self.target_blur.bind(gl);
+                gl.use_program(Some(program.program));
+                gl.bind_texture(glow::TEXTURE_2D, self.target_ao.color(0));
+                program.set_i32(gl, "aoTex", 0).unwrap();
+                program.set_i32(gl, "blurRadius", self.blur_radius).unwrap();
+                gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            }
+            // composite
+            {
+                let program = program_composite;
+                self.target_scene.end(gl);
+                gl.use_program(Some(program.program));
+                for (unit, (tex, name)) in [
+                    (self.target_scene.color(0), "colorTex"),
+                    (self.target_scene.depth(), "depthTex"),
+                    (self.target_blur.color(0), "aoTex"),
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    gl.active_texture(glow::TEXTURE0 + unit as u32);
+                    gl.bind_texture(glow::TEXTURE_2D, tex);
+                    program.set_i32(gl, name, unit as i32).unwrap();
+                }
+                program.set_f32(gl, "strength", &[self.strength]).unwrap();
+                // write the depth as well so that the later drawing is occluded by the meshes
+                gl.enable(glow::DEPTH_TEST);
+                gl.depth_func(glow::ALWAYS);
+                gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                gl.active_texture(glow::TEXTURE0);
+            }
+            gl.bind_vertex_array(None);
+            gl.depth_func(depth_func);
+            if !is_depth_test {
+                gl.disable(glow::DEPTH_TEST);
+            }
+            if is_blend {
+                gl.enable(glow::BLEND);
+            }
+        }
+    }
+
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        self.target_scene.destroy(gl);
+        self.target_ao.destroy(gl);
+        self.target_blur.destroy(gl);
+        for program in [
+            self.program_ao.take(),
+            self.program_blur.take(),
+            self.program_composite.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            program.destroy(gl);
+        }
+        unsafe {
+            if let Some(vao) = self.vertex_array.take() {
+                gl.delete_vertex_array(vao);
+            }
+        }
+    }
+}
+
+impl Default for Ssao {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// points inside the unit hemisphere around +Z, denser near the origin.
+/// A fixed linear congruential generator is used so that the result is deterministic
+fn sample_hemisphere(num_sample: usize) -> Vec<f32> {
+    let mut state = 12345u32;
+    let mut rand = || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as f32 / (1u32 << 24) as f32
+    };
+    let mut kernel = Vec::<f32>::with_capacity(num_sample * 3);
+    for i in 0..num_sample {
+        let v = [rand() * 2.0 - 1.0, rand() * 2.0 - 1.0, rand()];
+        let v = del_geo_core::vec3::normalize(&v);
+        let s = i as f32 / num_sample as f32;
+        let s = 0.1 + 0.9 * s * s;
+        let r = rand() * s;
+        kernel.extend_from_slice(&[v[0] * r, v[1] * r, v[2] * r]);
+    }
+    kernel
+}
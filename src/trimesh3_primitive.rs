@@ -0,0 +1,361 @@
+//! generators of triangle meshes of the primitive shapes with normals and UVs
+//!
+//! The shapes except [`aabb3`] (placed at the given box) are centered at the origin,
+//! and the axis of the rotational shapes is +Y.
+//! The output can be fed to `drawer_elem2vtx_vtx2xyz` (`tri2vtx` and `vtx2xyz` with `ndim=3`)
+//! and `drawer_elem2vtx_vtx2xyz_vtx2uv`.
+
+/// triangle mesh with per-vertex normal and texture coordinate
+#[derive(Default, Clone)]
+pub struct TriMesh3 {
+    pub tri2vtx: Vec<usize>,
+    pub vtx2xyz: Vec<f32>,
+    pub vtx2nrm: Vec<f32>,
+    pub vtx2uv: Vec<f32>,
+}
+
+impl TriMesh3 {
+    pub fn num_vtx(&self) -> usize {
+        self.vtx2xyz.len() / 3
+    }
+
+    fn push_vtx(&mut self, xyz: [f32; 3], nrm: [f32; 3], uv: [f32; 2]) {
+        self.vtx2xyz.extend_from_slice(&xyz);
+        self.vtx2nrm.extend_from_slice(&nrm);
+        self.vtx2uv.extend_from_slice(&uv);
+    }
+
+    /// add the triangles of a grid of `(nu+1) * (nv+1)` vertices added in the row-major order
+    /// starting from the vertex `i0_vtx`
+    fn push_grid_tris(&mut self, i0_vtx: usize, nu: usize, nv: usize) {
+        for iv in 0..nv {
+            for iu in 0..nu {
+                let i00 = i0_vtx + iv * (nu + 1) + iu;
+                let i10 = i00 + 1;
+                let i01 = i00 + nu + 1;
+                let i11 = i01 + 1;
+                self.tri2vtx
+                    .extend_from_slice(&[i00, i10, i11, i00, i11, i01]);
+            }
+        }
+    }
+
+    /// add the grid `origin + u * du + v * dv` for `u,v` in [0,1] facing `du x dv`
+    fn push_rect(&mut self, origin: &[f32; 3], du: &[f32; 3], dv: &[f32; 3], nu: usize, nv: usize) {
+        use del_geo_core::vec3;
+        let nrm = vec3::normalize(&vec3::cross(du, dv));
+        let i0_vtx = self.num_vtx();
+        for iv in 0..=nv {
+            for iu in 0..=nu {
+                let (u, v) = (iu as f32 / nu as f32, iv as f32 / nv as f32);
+                let p = vec3::add_three(origin, &vec3::scale(du, u), &vec3::scale(dv, v));
+                self.push_vtx(p, nrm, [u, v]);
+            }
+        }
+        self.push_grid_tris(i0_vtx, nu, nv);
+    }
+}
+
+/// rectangle in the XZ-plane facing +Y
+pub fn plane(size_x: f32, size_z: f32, ndiv_x: usize, ndiv_z: usize) -> TriMesh3 {
+    let mut m = TriMesh3::default();
+    m.push_rect(
+        &[-0.5 * size_x, 0., 0.5 * size_z],
+        &[size_x, 0., 0.],
+        &[0., 0., -size_z],
+        ndiv_x.max(1),
+        ndiv_z.max(1),
+    );
+    m
+}
+
+/// axis-aligned box `aabb = [min_x, min_y, min_z, max_x, max_y, max_z]`.
+/// The vertices are not shared between the faces to have the face normals
+pub fn aabb3(aabb: &[f32; 6], ndiv: usize) -> TriMesh3 {
+    let n = ndiv.max(1);
+    let (p0, p1) = ([aabb[0], aabb[1], aabb[2]], [aabb[3], aabb[4], aabb[5]]);
+    let d = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+    let mut m = TriMesh3::default();
+    // -X, +X
+    m.push_rect(&p0, &[0., 0., d[2]], &[0., d[1], 0.], n, n);
+    m.push_rect(
+        &[p1[0], p0[1], p1[2]],
+        &[0., 0., -d[2]],
+        &[0., d[1], 0.],
+        n,
+        n,
+    );
+    // -Y, +Y
+    m.push_rect(&p0, &[d[0], 0., 0.], &[0., 0., d[2]], n, n);
+    m.push_rect(
+        &[p0[0], p1[1], p1[2]],
+        &[d[0], 0., 0.],
+        &[0., 0., -d[2]],
+        n,
+        n,
+    );
+    // -Z, +Z
+    m.push_rect(
+        &[p1[0], p0[1], p0[2]],
+        &[-d[0], 0., 0.],
+        &[0., d[1], 0.],
+        n,
+        n,
+    );
+    m.push_rect(
+        &[p0[0], p0[1], p1[2]],
+        &[d[0], 0., 0.],
+        &[0., d[1], 0.],
+        n,
+        n,
+    );
+    m
+}
+
+/// UV sphere. `u` goes around +Y and `v` goes from the south pole to the north pole.
+/// The rows at the poles have one triangle per segment to avoid the degenerate triangles
+pub fn sphere(radius: f32, ndiv_longitude: usize, ndiv_latitude: usize) -> TriMesh3 {
+    let (nu, nv) = (ndiv_longitude.max(3), ndiv_latitude.max(2));
+    let mut m = TriMesh3::default();
+    for iv in 0..=nv {
+        let v = iv as f32 / nv as f32;
+        let phi = (v - 0.5) * std::f32::consts::PI;
+        for iu in 0..=nu {
+            let u = iu as f32 / nu as f32;
+            let theta = u * 2.0 * std::f32::consts::PI;
+            let n = [phi.cos() * theta.cos(), phi.sin(), -phi.cos() * theta.sin()];
+            m.push_vtx([n[0] * radius, n[1] * radius, n[2] * radius], n, [u, v]);
+        }
+    }
+    for iv in 0..nv {
+        for iu in 0..nu {
+            let i00 = iv * (nu + 1) + iu;
+            let (i10, i01) = (i00 + 1, i00 + nu + 1);
+            let i11 = i01 + 1;
+            if iv != 0 {
+                m.tri2vtx.extend_from_slice(&[i00, i10, i11]);
+            }
+            if iv != nv - 1 {
+                m.tri2vtx.extend_from_slice(&[i00, i11, i01]);
+            }
+        }
+    }
+    m
+}
+
+/// cylinder of `height` along +Y. The caps are added if `is_capped`
+pub fn cylinder(
+    radius: f32,
+    height: f32,
+    ndiv_circumference: usize,
+    ndiv_height: usize,
+    is_capped: bool,
+) -> TriMesh3 {
+    let (nu, nv) = (ndiv_circumference.max(3), ndiv_height.max(1));
+    let mut m = TriMesh3::default();
+    for iv in 0..=nv {
+        let v = iv as f32 / nv as f32;
+        for iu in 0..=nu {
+            let u = iu as f32 / nu as f32;
+            let theta = u * 2.0 * std::f32::consts::PI;
+            let n = [theta.cos(), 0., -theta.sin()];
+            let y = (v - 0.5) * height;
+            m.push_vtx([n[0] * radius, y, n[2] * radius], n, [u, v]);
+        }
+    }
+    m.push_grid_tris(0, nu, nv);
+    if is_capped {
+        push_disk(&mut m, radius, 0.5 * height, nu, true);
+        push_disk(&mut m, radius, -0.5 * height, nu, false);
+    }
+    m
+}
+
+/// cone with the base at `y=-height/2` and the apex at `y=height/2`.
+/// The apex is duplicated for each segment with the normal in the middle of the segment.
+/// The cone of zero height is a disk facing +Y
+pub fn cone(radius: f32, height: f32, ndiv_circumference: usize, is_capped: bool) -> TriMesh3 {
+    let nu = ndiv_circumference.max(3);
+    let mut m = TriMesh3::default();
+    // the normal of the side is perpendicular to the slant (radius, -height)
+    let len = (radius * radius + height * height).sqrt();
+    let (nr, ny) = if len > 0.0 {
+        (height / len, radius / len)
+    } else {
+        (0.0, 1.0)
+    };
+    let dir = |u: f32| {
+        let theta = u * 2.0 * std::f32::consts::PI;
+        (theta.cos(), -theta.sin())
+    };
+    for iu in 0..=nu {
+        let u = iu as f32 / nu as f32;
+        let (c, s) = dir(u);
+        m.push_vtx(
+            [c * radius, -0.5 * height, s * radius],
+            [c * nr, ny, s * nr],
+            [u, 0.],
+        );
+    }
+    for iu in 0..nu {
+        let u = (iu as f32 + 0.5) / nu as f32;
+        let (c, s) = dir(u);
+        m.push_vtx([0., 0.5 * height, 0.], [c * nr, ny, s * nr], [u, 1.]);
+        m.tri2vtx.extend_from_slice(&[iu, iu + 1, nu + 1 + iu]);
+    }
+    if is_capped {
+        push_disk(&mut m, radius, -0.5 * height, nu, false);
+    }
+    m
+}
+
+/// disk in the XZ-plane facing +Y
+pub fn disk(radius: f32, ndiv_circumference: usize) -> TriMesh3 {
+    let mut m = TriMesh3::default();
+    push_disk(&mut m, radius, 0., ndiv_circumference.max(3), true);
+    m
+}
+
+/// add a disk at the height `y` facing +Y if `is_up` otherwise -Y
+fn push_disk(m: &mut TriMesh3, radius: f32, y: f32, ndiv: usize, is_up: bool) {
+    let nrm = if is_up { [0., 1., 0.] } else { [0., -1., 0.] };
+    let i_center = m.num_vtx();
+    m.push_vtx([0., y, 0.], nrm, [0.5, 0.5]);
+    for iu in 0..ndiv {
+        let theta = iu as f32 / ndiv as f32 * 2.0 * std::f32::consts::PI;
+        let (c, s) = (theta.cos(), -theta.sin());
+        m.push_vtx(
+            [c * radius, y, s * radius],
+            nrm,
+            [0.5 + 0.5 * c, 0.5 - 0.5 * s],
+        );
+    }
+    for iu in 0..ndiv {
+        let i0 = i_center + 1 + iu;
+        let i1 = i_center + 1 + (iu + 1) % ndiv;
+        if is_up {
+            m.tri2vtx.extend_from_slice(&[i_center, i0, i1]);
+        } else {
+            m.tri2vtx.extend_from_slice(&[i_center, i1, i0]);
+        }
+    }
+}
+
+/// the 12 edges of the unit cube `[0,1]^3` as lines for `glow::LINES`
+pub fn unit_cube_edges() -> (Vec<usize>, Vec<f32>) {
+    let mut vtx2xyz = Vec::<f32>::with_capacity(24);
+    for i in 0..8 {
+        vtx2xyz.extend_from_slice(&[(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32]);
+    }
+    let mut edge2vtx = Vec::<usize>::with_capacity(24);
+    for i in 0..8usize {
+        for bit in [1, 2, 4] {
+            if i & bit == 0 {
+                edge2vtx.extend_from_slice(&[i, i | bit]);
+            }
+        }
+    }
+    (edge2vtx, vtx2xyz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// every triangle is non-degenerate and faces the same side as its vertex normals,
+    /// and the normals have the unit length
+    fn check_mesh(m: &TriMesh3) {
+        use del_geo_core::vec3;
+        let num_vtx = m.num_vtx();
+        assert_eq!(m.vtx2nrm.len(), num_vtx * 3);
+        assert_eq!(m.vtx2uv.len(), num_vtx * 2);
+        assert_eq!(m.tri2vtx.len() % 3, 0);
+        let xyz = |i: usize| arrayref::array_ref![m.vtx2xyz, i * 3, 3];
+        let nrm = |i: usize| arrayref::array_ref![m.vtx2nrm, i * 3, 3];
+        for i_vtx in 0..num_vtx {
+            assert!((vec3::norm(nrm(i_vtx)) - 1.0).abs() < 1.0e-5);
+        }
+        for tri in m.tri2vtx.chunks(3) {
+            assert!(tri.iter().all(|&i_vtx| i_vtx < num_vtx));
+            let (p0, p1, p2) = (xyz(tri[0]), xyz(tri[1]), xyz(tri[2]));
+            let n = vec3::cross(&vec3::sub(p1, p0), &vec3::sub(p2, p0));
+            assert!(vec3::norm(&n) > 1.0e-6, "degenerate triangle {tri:?}");
+            for &i_vtx in tri {
+                assert!(vec3::dot(&n, nrm(i_vtx)) > 0.0, "flipped triangle {tri:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_primitives() {
+        let m = plane(2.0, 1.0, 3, 2);
+        check_mesh(&m);
+        assert_eq!((m.num_vtx(), m.tri2vtx.len() / 3), (4 * 3, 3 * 2 * 2));
+        assert_eq!(&m.vtx2nrm[0..3], &[0., 1., 0.]);
+        //
+        let m = aabb3(&[-1., -2., -3., 1., 2., 3.], 2);
+        check_mesh(&m);
+        assert_eq!((m.num_vtx(), m.tri2vtx.len() / 3), (6 * 9, 6 * 8));
+        //
+        let m = sphere(1.5, 8, 4);
+        check_mesh(&m);
+        assert_eq!(
+            (m.num_vtx(), m.tri2vtx.len() / 3),
+            (9 * 5, 2 * 8 * 4 - 2 * 8)
+        );
+        //
+        let m = cylinder(1.0, 2.0, 8, 2, true);
+        check_mesh(&m);
+        assert_eq!(
+            (m.num_vtx(), m.tri2vtx.len() / 3),
+            (9 * 3 + 2 * 9, 2 * 8 * 2 + 2 * 8)
+        );
+        //
+        let m = cone(1.0, 2.0, 8, true);
+        check_mesh(&m);
+        assert_eq!((m.num_vtx(), m.tri2vtx.len() / 3), (9 + 8 + 9, 8 + 8));
+        //
+        // the flat cone has the normals of the disk
+        let m = cone(1.0, 0.0, 8, false);
+        check_mesh(&m);
+        for nrm in m.vtx2nrm.chunks(3) {
+            assert!(
+                del_geo_core::edge3::length(arrayref::array_ref![nrm, 0, 3], &[0., 1., 0.])
+                    < 1.0e-6
+            );
+        }
+        //
+        let m = disk(1.0, 6);
+        check_mesh(&m);
+        assert_eq!((m.num_vtx(), m.tri2vtx.len() / 3), (7, 6));
+    }
+
+    /// the closed shapes centered at the origin face outward
+    #[test]
+    fn test_outward() {
+        use del_geo_core::vec3;
+        for m in [
+            aabb3(&[-1., -1., -1., 1., 1., 1.], 1),
+            sphere(1.0, 6, 3),
+            cylinder(1.0, 1.0, 6, 1, true),
+            cone(1.0, 1.0, 6, true),
+        ] {
+            for tri in m.tri2vtx.chunks(3) {
+                let p = [tri[0], tri[1], tri[2]].map(|i| arrayref::array_ref![m.vtx2xyz, i * 3, 3]);
+                let n = vec3::cross(&vec3::sub(p[1], p[0]), &vec3::sub(p[2], p[0]));
+                let c = vec3::add_three(p[0], p[1], p[2]);
+                assert!(vec3::dot(&n, &c) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_cube_edges() {
+        let (edge2vtx, vtx2xyz) = unit_cube_edges();
+        assert_eq!((edge2vtx.len(), vtx2xyz.len()), (24, 24));
+        // each edge connects the vertices differing in exactly one coordinate
+        for e in edge2vtx.chunks(2) {
+            assert_eq!((e[0] ^ e[1]).count_ones(), 1);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! checks of the shapes and the index ranges of the arrays passed to the drawers
+//!
+//! The checks of the lengths and the indices always run because an array shorter than the
+//! upload or an index beyond the vertices makes the GPU (or the CPU) read out of bounds.
+//! The checks of the values (e.g., [`finite`]) only run in the debug builds or with the
+//! cargo feature `validate`, so the release build does not pay for them.
+
+/// true if the checks of the values run
+pub const IS_ENABLED: bool = cfg!(any(debug_assertions, feature = "validate"));
+
+/// `len` is a multiple of `unit` (e.g., `vtx2xyz.len()` and the dimension)
+pub fn multiple_of(name: &str, len: usize, unit: usize) -> Result<(), String> {
+    if unit == 0 {
+        return Err(format!("{name}: the number of components is zero"));
+    }
+    if !len.is_multiple_of(unit) {
+        return Err(format!(
+            "{name}: the length {len} is not a multiple of {unit}"
+        ));
+    }
+    Ok(())
+}
+
+/// `len` is `expected` (e.g., the colors of the triangles)
+pub fn length(name: &str, len: usize, expected: usize) -> Result<(), String> {
+    if len != expected {
+        return Err(format!(
+            "{name}: the length is {len} but {expected} is expected"
+        ));
+    }
+    Ok(())
+}
+
+/// all the indices are less than `num_vtx`
+pub fn index_range<T>(name: &str, elem2vtx: &[T], num_vtx: usize) -> Result<(), String>
+where
+    T: 'static + Copy + num_traits::AsPrimitive<usize>,
+{
+    if let Some((i, i_vtx)) = elem2vtx
+        .iter()
+        .map(|&i_vtx| i_vtx.as_())
+        .enumerate()
+        .find(|&(_, i_vtx)| i_vtx >= num_vtx)
+    {
+        return Err(format!(
+            "{name}: the index {i_vtx} at {i} is out of range for {num_vtx} vertices"
+        ));
+    }
+    Ok(())
+}
+
+/// the maximum index of the element buffer (if known) is less than `num_vtx`
+pub fn max_index(name: &str, max_index: Option<u32>, num_vtx: usize) -> Result<(), String> {
+    match max_index {
+        Some(max_index) if max_index as usize >= num_vtx => Err(format!(
+            "{name}: the index {max_index} is out of range for {num_vtx} vertices"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// offsets of the CSR format (e.g., `elem2idx` of the polygons) start at 0, never decrease,
+/// and end at `num_idx`. Empty `elem2idx` is the one without any element
+pub fn offsets(name: &str, elem2idx: &[usize], num_idx: usize) -> Result<(), String> {
+    let Some(&first) = elem2idx.first() else {
+        return length("idx", num_idx, 0);
+    };
+    if first != 0 {
+        return Err(format!(
+            "{name}: the first offset is {first} but 0 is expected"
+        ));
+    }
+    if let Some(i) = elem2idx.windows(2).position(|w| w[0] > w[1]) {
+        return Err(format!(
+            "{name}: the offset decreases from {} to {} at {i}",
+            elem2idx[i],
+            elem2idx[i + 1]
+        ));
+    }
+    let last = elem2idx[elem2idx.len() - 1];
+    if last != num_idx {
+        return Err(format!(
+            "{name}: the last offset is {last} but the number of the indices is {num_idx}"
+        ));
+    }
+    Ok(())
+}
+
+/// all the values are finite (e.g., the coordinates of the vertices).
+/// Only runs if [`IS_ENABLED`] as it scans all the values
+pub fn finite(name: &str, values: &[f32]) -> Result<(), String> {
+    if !IS_ENABLED {
+        return Ok(());
+    }
+    if let Some((i, v)) = values.iter().enumerate().find(|(_, v)| !v.is_finite()) {
+        return Err(format!("{name}: the value {v} at {i} is not finite"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape() {
+        assert!(multiple_of("a", 6, 3).is_ok());
+        assert!(multiple_of("a", 7, 3).is_err());
+        assert!(multiple_of("a", 0, 0).is_err());
+        assert!(length("a", 3, 3).is_ok());
+        assert!(length("a", 2, 3).is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        assert!(index_range("a", &[0usize, 2, 1], 3).is_ok());
+        assert!(index_range("a", &[0u32, 3], 3).is_err());
+        assert!(max_index("a", Some(2), 3).is_ok());
+        assert!(max_index("a", Some(3), 3).is_err());
+        assert!(max_index("a", None, 0).is_ok());
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert!(offsets("a", &[0, 3, 3, 7], 7).is_ok());
+        assert!(offsets("a", &[], 0).is_ok());
+        assert!(offsets("a", &[], 1).is_err());
+        assert!(offsets("a", &[1, 3], 3).is_err());
+        assert!(offsets("a", &[0, 4, 3, 7], 7).is_err());
+        assert!(offsets("a", &[0, 3], 4).is_err());
+    }
+
+    #[test]
+    fn test_finite() {
+        assert!(finite("a", &[0.0, -1.0, 1.0e30]).is_ok());
+        let res = finite("a", &[0.0, f32::NAN]);
+        assert_eq!(res.is_err(), IS_ENABLED);
+    }
+}
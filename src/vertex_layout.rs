@@ -0,0 +1,243 @@
+//! description of the vertex attributes laid out in one or more vertex buffers
+//!
+//! ```ignore
+//! // interleaved xy + rgb in the buffer 0, and per-instance offset in the buffer 1
+//! let layout = del_glow::vertex_layout::VertexLayout::new()
+//!     .buffer()
+//!     .attrib("xyzIn", 2, AttribType::F32)
+//!     .attrib("rgbIn", 3, AttribType::F32)
+//!     .buffer()
+//!     .attrib("offsetIn", 2, AttribType::F32)
+//!     .divisor(1);
+//! ```
+
+use glow::HasContext;
+
+/// type of the components of an attribute in the buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttribType {
+    /// `float` in the shader
+    F32,
+    /// `float` in the shader in [0,1] (e.g., 8-bit color)
+    U8Normalized,
+    /// `float` in the shader in [-1,1] (e.g., packed normal)
+    I8Normalized,
+    /// `float` in the shader in [0,1]
+    U16Normalized,
+    /// `uint` in the shader
+    U8,
+    /// `uint` in the shader
+    U16,
+    /// `uint` in the shader
+    U32,
+    /// `int` in the shader
+    I32,
+}
+
+impl AttribType {
+    pub fn size_of(&self) -> usize {
+        match self {
+            AttribType::F32 | AttribType::U32 | AttribType::I32 => 4,
+            AttribType::U16Normalized | AttribType::U16 => 2,
+            AttribType::U8Normalized | AttribType::I8Normalized | AttribType::U8 => 1,
+        }
+    }
+
+    pub fn gl_type(&self) -> u32 {
+        match self {
+            AttribType::F32 => glow::FLOAT,
+            AttribType::U8Normalized | AttribType::U8 => glow::UNSIGNED_BYTE,
+            AttribType::I8Normalized => glow::BYTE,
+            AttribType::U16Normalized | AttribType::U16 => glow::UNSIGNED_SHORT,
+            AttribType::U32 => glow::UNSIGNED_INT,
+            AttribType::I32 => glow::INT,
+        }
+    }
+
+    /// true if the shader reads the attribute as an integer (`glVertexAttribIPointer`)
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            AttribType::U8 | AttribType::U16 | AttribType::U32 | AttribType::I32
+        )
+    }
+
+    pub fn is_normalized(&self) -> bool {
+        matches!(
+            self,
+            AttribType::U8Normalized | AttribType::I8Normalized | AttribType::U16Normalized
+        )
+    }
+}
+
+/// attribute specified by the name in the shader or by `layout (location = N)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttribLocation {
+    Name(String),
+    Location(u32),
+}
+
+impl From<&str> for AttribLocation {
+    fn from(name: &str) -> Self {
+        AttribLocation::Name(name.to_string())
+    }
+}
+
+impl From<u32> for AttribLocation {
+    fn from(location: u32) -> Self {
+        AttribLocation::Location(location)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VertexAttrib {
+    pub location: AttribLocation,
+    /// number of the components (1 to 4)
+    pub num_comp: usize,
+    pub atype: AttribType,
+    /// byte offset from the beginning of the vertex
+    pub offset: usize,
+}
+
+/// an attribute in an uploaded vertex buffer (e.g., to draw another pass from the same buffer)
+#[derive(Clone, Copy, Debug)]
+pub struct AttribView {
+    pub vbo: glow::NativeBuffer,
+    pub num_comp: usize,
+    pub atype: AttribType,
+    /// byte size of one vertex
+    pub stride: usize,
+    /// byte offset from the beginning of the vertex
+    pub offset: usize,
+    /// number of the vertices drawn from the buffer
+    pub num_vtx: usize,
+}
+
+/// attributes stored in one buffer
+#[derive(Clone, Debug, Default)]
+pub struct BufferLayout {
+    pub attribs: Vec<VertexAttrib>,
+    /// byte size of one vertex. Zero means tightly packed
+    pub stride: usize,
+    /// 0 for per-vertex data, N for the data advancing every N instances
+    pub divisor: u32,
+}
+
+impl BufferLayout {
+    /// byte size of one vertex
+    pub fn stride(&self) -> usize {
+        if self.stride != 0 {
+            return self.stride;
+        }
+        self.attribs
+            .iter()
+            .map(|a| a.offset + a.num_comp * a.atype.size_of())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VertexLayout {
+    pub buffers: Vec<BufferLayout>,
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        VertexLayout { buffers: vec![] }
+    }
+
+    /// start the description of the next buffer
+    pub fn buffer(mut self) -> Self {
+        self.buffers.push(BufferLayout::default());
+        self
+    }
+
+    /// append an attribute to the current buffer right after the previous one
+    pub fn attrib<L: Into<AttribLocation>>(
+        mut self,
+        location: L,
+        num_comp: usize,
+        atype: AttribType,
+    ) -> Self {
+        assert!((1..=4).contains(&num_comp), "1 to 4 components");
+        if self.buffers.is_empty() {
+            self.buffers.push(BufferLayout::default());
+        }
+        let buffer = self.buffers.last_mut().unwrap();
+        let offset = buffer
+            .attribs
+            .iter()
+            .map(|a| a.offset + a.num_comp * a.atype.size_of())
+            .max()
+            .unwrap_or(0);
+        buffer.attribs.push(VertexAttrib {
+            location: location.into(),
+            num_comp,
+            atype,
+            offset,
+        });
+        self
+    }
+
+    /// set the stride of the current buffer explicitly (e.g., padding at the end of the vertex)
+    pub fn stride(mut self, stride: usize) -> Self {
+        self.buffers.last_mut().expect("call buffer() first").stride = stride;
+        self
+    }
+
+    /// make the current buffer per-instance
+    pub fn divisor(mut self, divisor: u32) -> Self {
+        self.buffers
+            .last_mut()
+            .expect("call buffer() first")
+            .divisor = divisor;
+        self
+    }
+
+    /// set the attribute pointers of the buffer `i_buffer` bound to `vbo`.
+    /// The vertex array must be bound. The attributes not active in `program` are skipped
+    pub fn apply(
+        &self,
+        gl: &glow::Context,
+        program: &crate::shader_program::ShaderProgram,
+        i_buffer: usize,
+        vbo: glow::NativeBuffer,
+    ) {
+        let buffer = &self.buffers[i_buffer];
+        let stride = buffer.stride() as i32;
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            for attrib in &buffer.attribs {
+                let location = match &attrib.location {
+                    AttribLocation::Location(location) => *location,
+                    AttribLocation::Name(name) => match program.attrib_location(name) {
+                        Some(location) => location,
+                        None => continue, // optimized out
+                    },
+                };
+                gl.enable_vertex_attrib_array(location);
+                if attrib.atype.is_integer() {
+                    gl.vertex_attrib_pointer_i32(
+                        location,
+                        attrib.num_comp as i32,
+                        attrib.atype.gl_type(),
+                        stride,
+                        attrib.offset as i32,
+                    );
+                } else {
+                    gl.vertex_attrib_pointer_f32(
+                        location,
+                        attrib.num_comp as i32,
+                        attrib.atype.gl_type(),
+                        attrib.atype.is_normalized(),
+                        stride,
+                        attrib.offset as i32,
+                    );
+                }
+                gl.vertex_attrib_divisor(location, buffer.divisor);
+            }
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+    }
+}